@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::thread;
+
+extern crate sdl2;
+
+use sdl2::Sdl;
+use sdl2::EventPump;
+use sdl2::event::Event;
+
+mod display;
+mod audio;
+mod input;
+
+use display::Display;
+use audio::Audio;
+use input::Input;
+
+use gbc_emulator::{Machine, CartridgeHeader, HeaderError, FRAME_PERIOD};
+
+pub use input::InputConfig;
+
+// Battery backed RAM is flushed to disk at most this often while running
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+// SDL window, input and audio driving the emulated machine in real time
+pub struct GameBoyColor {
+    sdl_context: Sdl,
+    sdl_event_pump: EventPump,
+
+    input: Input,
+    display: Display,
+    audio: Audio,
+
+    machine: Machine,
+
+    save_path: Option<PathBuf>
+}
+
+impl GameBoyColor {
+
+    // Takes over a machine already configured, but with no ROM loaded yet
+    pub fn new(mut machine: Machine) -> Self {
+        let sdl_context = sdl2::init().unwrap();
+        let sdl_event_pump = sdl_context.event_pump().unwrap();
+
+        let display = Display::new(&sdl_context);
+        let input = Input::new(&sdl_context);
+        let audio = Audio::new(&sdl_context);
+
+        machine.set_sample_rate(audio.device_rate());
+
+        Self {
+            sdl_context,
+            sdl_event_pump,
+            input,
+            display,
+            audio,
+            machine,
+            save_path: None
+        }
+    }
+
+    // Must be called before loading the ROM for the per ROM bindings to apply
+    pub fn set_input_config(&mut self, config: InputConfig) {
+        self.input.set_config(config);
+    }
+
+    pub fn load_rom(&mut self, rom: Vec<u8>, rom_path: &Path) -> Result<CartridgeHeader, HeaderError> {
+        let header = self.machine.load_rom(rom)?;
+
+        // Battery backed carts keep their RAM in <rom>.sav
+        self.save_path = if self.machine.has_battery() {
+            let save_path = rom_path.with_extension("sav");
+
+            if let Ok(save_data) = fs::read(&save_path) {
+                self.machine.load_save_data(&save_data);
+            }
+
+            Some(save_path)
+        } else {
+            None
+        };
+
+        self.input.select_rom(&header.title);
+
+        Ok(header)
+    }
+
+    fn save(&mut self) {
+        if let (Some(save_path), Some(save_data)) = (&self.save_path, self.machine.save_data()) {
+            if let Err(error) = fs::write(save_path, save_data) {
+                eprintln!("Error writing {}: {}", save_path.display(), error);
+            }
+        }
+    }
+
+    pub fn run(&mut self) {
+        let mut frame_deadline = Instant::now();
+        let mut save_timer = Instant::now();
+
+        'main_loop: loop {
+            // Check input events
+            for event in self.sdl_event_pump.poll_iter() {
+                match event {
+                    Event::Quit { .. } => break 'main_loop,
+                    _ => self.input.handle_event(&event, &mut self.machine)
+                }
+            }
+
+            self.machine.run_frame();
+
+            self.display.update(self.machine.framebuffer());
+
+            let samples = self.machine.take_audio_samples();
+            self.audio.queue_samples(&samples);
+
+            // Periodic flush so a crash doesn't lose the progress
+            if save_timer.elapsed() >= SAVE_INTERVAL && self.machine.is_save_dirty() {
+                save_timer = Instant::now();
+                self.save();
+            }
+
+            // Frame pacing, when running behind the lost time isn't made up
+            frame_deadline += FRAME_PERIOD;
+
+            let now = Instant::now();
+
+            if frame_deadline > now {
+                thread::sleep(frame_deadline - now);
+            } else {
+                frame_deadline = now;
+            }
+        }
+
+        self.save();
+    }
+
+}
@@ -0,0 +1,100 @@
+extern crate sdl2;
+
+use sdl2::Sdl;
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+
+use gbc_emulator::DEFAULT_SAMPLE_RATE;
+
+const CHANNELS: u8 = 2;
+const DEVICE_BUFFER_FRAMES: u16 = 1024;
+
+// Frames kept queued in the device, ~50 ms at 48 kHz
+const TARGET_QUEUED_FRAMES: u32 = 2400;
+
+// Maximum deviation from the nominal rate, small enough not to be heard
+const MAX_RATE_DELTA: f64 = 0.005;
+
+pub struct Audio {
+    queue: AudioQueue<f32>,
+
+    // Resampler state, fractional position from the last frame of the previous batch
+    position: f64,
+    last_frame: [f32; 2]
+}
+
+impl Audio {
+
+    pub fn new(sdl_context: &Sdl) -> Self {
+        let audio_subsystem = sdl_context.audio().unwrap();
+
+        let desired_spec = AudioSpecDesired {
+            freq: Some(DEFAULT_SAMPLE_RATE as i32),
+            channels: Some(CHANNELS),
+            samples: Some(DEVICE_BUFFER_FRAMES)
+        };
+
+        let queue = audio_subsystem.open_queue::<f32, _>(None, &desired_spec).unwrap();
+        queue.resume();
+
+        Self {
+            queue,
+            position: 0.0,
+            last_frame: [0.0; 2]
+        }
+    }
+
+    // The APU produces samples at this rate and they are adjusted around it
+    pub fn device_rate(&self) -> u32 {
+        self.queue.spec().freq as u32
+    }
+
+    // Interleaved stereo samples at the device rate
+    pub fn queue_samples(&mut self, samples: &[f32]) {
+        let frames: Vec<[f32; 2]> = samples.chunks_exact(2)
+            .map(|frame| [frame[0], frame[1]])
+            .collect();
+
+        if frames.is_empty() {
+            return;
+        }
+
+        let queued_frames = self.queue.size() / (CHANNELS as u32 * std::mem::size_of::<f32>() as u32);
+
+        // Way ahead of the device, the batch is dropped instead of adding latency
+        if queued_frames > 4 * TARGET_QUEUED_FRAMES {
+            return;
+        }
+
+        // Dynamic rate control, the batch is shrunk when the queue is filling up and
+        // stretched when it's draining so the emulation timing jitter is absorbed
+        let fill = (queued_frames as f64 / (2 * TARGET_QUEUED_FRAMES) as f64).min(1.0);
+        let ratio = 1.0 + MAX_RATE_DELTA * (1.0 - 2.0 * fill);
+
+        // Input frames consumed per output frame
+        let step = 1.0 / ratio;
+
+        let mut output = Vec::with_capacity((frames.len() as f64 * ratio) as usize * 2 + 2);
+
+        // Linear interpolation, frame 0 is the last frame of the previous batch
+        while self.position < frames.len() as f64 {
+            let index = self.position as usize;
+            let fraction = self.position.fract() as f32;
+
+            let previous = if index == 0 { self.last_frame } else { frames[index - 1] };
+            let next = frames[index];
+
+            output.push(previous[0] + (next[0] - previous[0]) * fraction);
+            output.push(previous[1] + (next[1] - previous[1]) * fraction);
+
+            self.position += step;
+        }
+
+        self.position -= frames.len() as f64;
+        self.last_frame = frames[frames.len() - 1];
+
+        if let Err(error) = self.queue.queue_audio(&output) {
+            eprintln!("Error queueing audio: {}", error);
+        }
+    }
+
+}
@@ -0,0 +1,51 @@
+extern crate sdl2;
+
+use sdl2::Sdl;
+use sdl2::render::{WindowCanvas, Texture};
+use sdl2::pixels::PixelFormatEnum;
+
+use gbc_emulator::{FrameBuffer, LCD_WIDTH, LCD_HEIGHT};
+
+const WINDOW_SCALE: u32 = 4;
+
+pub struct Display {
+    canvas: WindowCanvas,
+
+    // Reused every frame, it lives as long as the canvas's renderer
+    texture: Texture
+}
+
+impl Display {
+
+    pub fn new(sdl_context: &Sdl) -> Self {
+        let video_subsystem = sdl_context.video().unwrap();
+
+        let window = video_subsystem.window(
+                "Game Boy Color",
+                LCD_WIDTH as u32 * WINDOW_SCALE,
+                LCD_HEIGHT as u32 * WINDOW_SCALE
+            )
+            .position_centered()
+            .build()
+            .unwrap();
+
+        let canvas = window.into_canvas().build().unwrap();
+        let texture = canvas.texture_creator()
+            .create_texture_streaming(PixelFormatEnum::RGB24, LCD_WIDTH as u32, LCD_HEIGHT as u32)
+            .unwrap();
+
+        Self {
+            canvas,
+            texture
+        }
+    }
+
+    pub fn update(&mut self, framebuffer: &FrameBuffer) {
+        self.texture.update(None, framebuffer, LCD_WIDTH * 3).unwrap();
+
+        // Draw canvas, the texture is stretched to the whole window
+        self.canvas.copy(&self.texture, None, None).unwrap();
+        self.canvas.present();
+    }
+
+}
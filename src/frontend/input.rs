@@ -0,0 +1,306 @@
+use std::fmt;
+use std::error::Error;
+
+use sdl2::Sdl;
+use sdl2::GameControllerSubsystem;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::controller::{self, Axis, GameController};
+
+use gbc_emulator::{Button, Machine};
+
+// Axis values past this point count as a pressed direction
+const AXIS_THRESHOLD: i16 = 16384;
+
+// Section holding the bindings used by every ROM
+const DEFAULT_SECTION: &str = "default";
+
+// Sections overriding the defaults for a single ROM title
+const ROM_SECTION_PREFIX: &str = "rom:";
+
+const DEFAULT_CONFIG: &str = "
+[default]
+right = key:Right, button:dpright, axis:leftx+
+left = key:Left, button:dpleft, axis:leftx-
+up = key:Up, button:dpup, axis:lefty-
+down = key:Down, button:dpdown, axis:lefty+
+a = key:X, button:a
+b = key:Z, button:b
+select = key:Backspace, button:back
+start = key:Return, button:start
+";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Binding {
+    Key(Keycode),
+    ControllerButton(controller::Button),
+    ControllerAxis(Axis, bool)
+}
+
+// Host inputs bound to each Game Boy button, indexed as Button::ALL
+type Bindings = [Vec<Binding>; 8];
+
+// Per ROM bindings, None keeps the default while an empty list unbinds the button
+type Overrides = [Option<Vec<Binding>>; 8];
+
+#[derive(Debug)]
+pub enum InputConfigError {
+    InvalidLine(usize),
+    UnknownButton(usize, String),
+    UnknownBinding(usize, String)
+}
+
+impl fmt::Display for InputConfigError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputConfigError::InvalidLine(line) => write!(f, "line {}: expected [section] or button = bindings", line),
+            InputConfigError::UnknownButton(line, name) => write!(f, "line {}: unknown button {}", line, name),
+            InputConfigError::UnknownBinding(line, name) => write!(f, "line {}: unknown binding {}", line, name)
+        }
+    }
+
+}
+
+impl Error for InputConfigError {}
+
+// Default bindings plus per ROM overrides, e.g.
+//
+//   [default]
+//   a = key:X, button:a
+//   up = key:Up, axis:lefty-
+//
+//   [rom:POKEMON RED]
+//   a = key:Space
+//   select =
+#[derive(Clone)]
+pub struct InputConfig {
+    default: Bindings,
+    overrides: Vec<(String, Overrides)>
+}
+
+impl InputConfig {
+
+    // Buttons not mentioned in the text keep their built-in bindings
+    pub fn parse(text: &str) -> Result<Self, InputConfigError> {
+        Self::parse_over(text, Self::default().default)
+    }
+
+    fn parse_over(text: &str, mut default: Bindings) -> Result<Self, InputConfigError> {
+        let mut overrides: Vec<(String, Overrides)> = Vec::new();
+
+        let mut section = DEFAULT_SECTION.to_string();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let (button_name, binding_names) = line.split_once('=')
+                .ok_or(InputConfigError::InvalidLine(line_number))?;
+
+            let button_name = button_name.trim();
+            let button = button_from_name(button_name)
+                .ok_or_else(|| InputConfigError::UnknownButton(line_number, button_name.to_string()))?;
+
+            let mut bindings = Vec::new();
+
+            for binding_name in binding_names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                let binding = binding_from_name(binding_name)
+                    .ok_or_else(|| InputConfigError::UnknownBinding(line_number, binding_name.to_string()))?;
+
+                bindings.push(binding);
+            }
+
+            // Bindings for a button replace the ones it had in the defaults
+            if section == DEFAULT_SECTION {
+                default[button_index(button)] = bindings;
+            } else if let Some(title) = section.strip_prefix(ROM_SECTION_PREFIX) {
+                let position = match overrides.iter().position(|(name, _)| name == title) {
+                    Some(position) => position,
+                    None => {
+                        overrides.push((title.to_string(), Overrides::default()));
+                        overrides.len() - 1
+                    }
+                };
+
+                overrides[position].1[button_index(button)] = Some(bindings);
+            } else {
+                return Err(InputConfigError::InvalidLine(line_number));
+            }
+        }
+
+        Ok(Self {
+            default,
+            overrides
+        })
+    }
+
+    // Bindings for a ROM title, falling back to the defaults per button
+    fn bindings(&self, title: &str) -> Bindings {
+        let mut bindings = self.default.clone();
+
+        if let Some((_, overrides)) = self.overrides.iter().find(|(name, _)| name == title) {
+            for (binding, overridden) in bindings.iter_mut().zip(overrides.iter()) {
+                if let Some(overridden) = overridden {
+                    *binding = overridden.clone();
+                }
+            }
+        }
+
+        bindings
+    }
+
+}
+
+impl Default for InputConfig {
+
+    fn default() -> Self {
+        Self::parse_over(DEFAULT_CONFIG, Bindings::default()).unwrap()
+    }
+
+}
+
+pub struct Input {
+    config: InputConfig,
+    bindings: Bindings,
+
+    // Host inputs currently held, a button is pressed while any of its bindings is
+    active: Vec<Binding>,
+
+    controller_subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>
+}
+
+impl Input {
+
+    pub fn new(sdl_context: &Sdl) -> Self {
+        let config = InputConfig::default();
+        let bindings = config.bindings("");
+
+        Self {
+            config,
+            bindings,
+            active: Vec::new(),
+            controller_subsystem: sdl_context.game_controller().unwrap(),
+            controllers: Vec::new()
+        }
+    }
+
+    pub fn set_config(&mut self, config: InputConfig) {
+        self.bindings = config.bindings("");
+        self.config = config;
+    }
+
+    // Applies the overrides for the loaded ROM
+    pub fn select_rom(&mut self, title: &str) {
+        self.bindings = self.config.bindings(title);
+    }
+
+    pub fn handle_event(&mut self, event: &Event, machine: &mut Machine) {
+        match *event {
+            Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+                self.set_binding(Binding::Key(keycode), true, machine);
+            },
+
+            Event::KeyUp { keycode: Some(keycode), .. } => {
+                self.set_binding(Binding::Key(keycode), false, machine);
+            },
+
+            Event::ControllerButtonDown { button, .. } => {
+                self.set_binding(Binding::ControllerButton(button), true, machine);
+            },
+
+            Event::ControllerButtonUp { button, .. } => {
+                self.set_binding(Binding::ControllerButton(button), false, machine);
+            },
+
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                self.set_binding(Binding::ControllerAxis(axis, true), value > AXIS_THRESHOLD, machine);
+                self.set_binding(Binding::ControllerAxis(axis, false), value < -AXIS_THRESHOLD, machine);
+            },
+
+            // SDL also reports the controllers connected at startup as added
+            Event::ControllerDeviceAdded { which, .. } => {
+                match self.controller_subsystem.open(which) {
+                    Ok(controller) => self.controllers.push(controller),
+                    Err(error) => eprintln!("Error opening controller {}: {}", which, error)
+                }
+            },
+
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.retain(|controller| controller.instance_id() != which);
+            },
+
+            _ => {}
+        }
+    }
+
+    fn set_binding(&mut self, binding: Binding, active: bool, machine: &mut Machine) {
+        let position = self.active.iter().position(|&other| other == binding);
+
+        match (position, active) {
+            (None, true) => self.active.push(binding),
+            (Some(position), false) => {
+                self.active.swap_remove(position);
+            },
+            // Axes report every small motion, nothing changed
+            _ => return
+        }
+
+        for (button, bindings) in Button::ALL.iter().zip(self.bindings.iter()) {
+            if bindings.contains(&binding) {
+                let pressed = bindings.iter().any(|bound| self.active.contains(bound));
+                machine.set_button(*button, pressed);
+            }
+        }
+    }
+
+}
+
+fn button_index(button: Button) -> usize {
+    Button::ALL.iter().position(|&other| other == button).unwrap()
+}
+
+fn button_from_name(name: &str) -> Option<Button> {
+    match name.to_ascii_lowercase().as_str() {
+        "right" => Some(Button::Right),
+        "left" => Some(Button::Left),
+        "up" => Some(Button::Up),
+        "down" => Some(Button::Down),
+        "a" => Some(Button::A),
+        "b" => Some(Button::B),
+        "select" => Some(Button::Select),
+        "start" => Some(Button::Start),
+        _ => None
+    }
+}
+
+// key:<SDL key name>, button:<SDL controller button>, axis:<SDL controller axis>[+-]
+fn binding_from_name(name: &str) -> Option<Binding> {
+    let (kind, value) = name.split_once(':')?;
+    let value = value.trim();
+
+    match kind.trim() {
+        "key" => Keycode::from_name(value).map(Binding::Key),
+        "button" => controller::Button::from_string(value).map(Binding::ControllerButton),
+        "axis" => {
+            let (axis, positive) = if let Some(axis) = value.strip_suffix('+') {
+                (axis, true)
+            } else {
+                (value.strip_suffix('-')?, false)
+            };
+
+            Axis::from_string(axis).map(|axis| Binding::ControllerAxis(axis, positive))
+        },
+        _ => None
+    }
+}
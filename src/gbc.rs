@@ -1,100 +1,355 @@
-use std::time::{Instant};
-
-extern crate sdl2;
-
-use sdl2::Sdl;
-use sdl2::EventPump;
-use sdl2::event::Event;
+use std::time::Duration;
 
 mod core;
 mod memory;
-mod display;
+mod timer;
+mod ppu;
+mod palette;
+mod dma;
+mod cartridge;
+mod model;
+mod joypad;
+mod apu;
+mod serial;
 
 use core::Core;
 use memory::Memory;
-use display::Display;
+use ppu::{Ppu, DOTS_PER_FRAME};
+use cartridge::Cartridge;
+
+pub use cartridge::{CartridgeHeader, HeaderError, CgbSupport};
+pub use model::{Model, BootRomError};
+pub use joypad::Button;
+pub use ppu::{FrameBuffer, LCD_WIDTH, LCD_HEIGHT};
+pub use apu::DEFAULT_SAMPLE_RATE;
+pub use serial::{SerialLink, TcpLink, SerialCapture, CaptureTarget};
 
 // Clock periods (ns)
 const SLOW_CLK_PERIOD: u128 = 238;
 const FAST_CLK_PERIOD: u128 = 119;
 
-pub struct GameBoyColor {
-    sdl_context: Sdl,
-    sdl_event_pump: EventPump,
+// Real time taken by a frame, the PPU runs one dot every two fast clock cycles
+pub const FRAME_PERIOD: Duration = Duration::from_nanos(DOTS_PER_FRAME as u64 * 2 * FAST_CLK_PERIOD as u64);
+
+// The emulated hardware, free of any frontend
+pub struct Machine {
+    model: Model,
 
     core: Core,
     memory: Memory,
-    display: Display
-}
+    ppu: Ppu,
 
-impl GameBoyColor {
+    // A frame was completed during the last step
+    frame_ready: bool,
 
-    pub fn new() -> Self {
-        let sdl_context = sdl2::init().unwrap();
-        let sdl_event_pump = sdl_context.event_pump().unwrap();
+    // Dots since the last frame, an LCD left off doesn't produce frames
+    frame_dots: u32
+}
 
-        let display = Display::new(&sdl_context);
+impl Machine {
 
+    pub fn new(model: Model) -> Self {
         Self {
-            sdl_context,
-            sdl_event_pump,
+            model,
             core: Core::new(),
-            memory: Memory::new(),
-            display
+            memory: Memory::new(model),
+            ppu: Ppu::new(),
+            frame_ready: false,
+            frame_dots: 0
+        }
+    }
+
+    // Machine with the ROM already inserted, ready to run
+    pub fn from_rom(model: Model, rom: Vec<u8>) -> Result<Self, HeaderError> {
+        let mut machine = Self::new(model);
+        machine.load_rom(rom)?;
+
+        Ok(machine)
+    }
+
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    pub fn set_color_correction(&mut self, enabled: bool) {
+        self.ppu.set_color_correction(enabled);
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.memory.set_sample_rate(sample_rate);
+    }
+
+    pub fn set_serial_link(&mut self, link: Box<dyn SerialLink>) {
+        self.memory.set_serial_link(link);
+    }
+
+    // Must be called before loading the ROM
+    pub fn load_boot_rom(&mut self, boot_rom: Vec<u8>) -> Result<(), BootRomError> {
+        if self.memory.cartridge().is_some() {
+            return Err(BootRomError::CartridgeLoaded);
+        }
+
+        if boot_rom.len() != self.model.boot_rom_size() {
+            return Err(BootRomError::InvalidSize(self.model, boot_rom.len()));
+        }
+
+        self.memory.load_boot_rom(boot_rom);
+
+        Ok(())
+    }
+
+    pub fn load_rom(&mut self, rom: Vec<u8>) -> Result<CartridgeHeader, HeaderError> {
+        let cartridge = Cartridge::new(rom)?;
+        let header = cartridge.header().clone();
+
+        self.memory.load_cartridge(cartridge);
+
+        // Without a boot ROM, start right where it would have left the machine
+        if !self.memory.has_boot_rom() {
+            let dmg_compatibility = self.model.is_cgb() && header.cgb_support == CgbSupport::None;
+
+            self.core.init_post_boot(self.model, dmg_compatibility);
+            self.memory.init_post_boot(dmg_compatibility);
+        }
+
+        Ok(header)
+    }
+
+    pub fn has_battery(&self) -> bool {
+        self.memory.cartridge().is_some_and(|cartridge| cartridge.has_battery())
+    }
+
+    pub fn is_save_dirty(&self) -> bool {
+        self.memory.cartridge().is_some_and(|cartridge| cartridge.is_save_dirty())
+    }
+
+    // Battery backed RAM, None if there is nothing to save
+    pub fn save_data(&mut self) -> Option<Vec<u8>> {
+        self.memory.cartridge_mut()
+            .filter(|cartridge| cartridge.has_battery())
+            .map(|cartridge| cartridge.save_data())
+    }
+
+    pub fn load_save_data(&mut self, save_data: &[u8]) {
+        if let Some(cartridge) = self.memory.cartridge_mut() {
+            cartridge.load_save_data(save_data);
         }
     }
 
-    pub fn load_rom(&mut self, rom: Vec<u8>) {
-        for (addr, byte) in rom.into_iter().enumerate() {
-            self.memory.write(addr as u16, byte);
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        self.memory.set_button(button, pressed);
+    }
+
+    pub fn framebuffer(&self) -> &FrameBuffer {
+        self.ppu.framebuffer()
+    }
+
+    pub fn is_frame_ready(&self) -> bool {
+        self.frame_ready
+    }
+
+    // Interleaved stereo samples produced since the last call
+    pub fn take_audio_samples(&mut self) -> Vec<f32> {
+        self.memory.take_audio_samples()
+    }
+
+    // CPU view of the address space, with the same side effects as the running program
+    pub fn read_memory(&self, addr: u16) -> u8 {
+        self.memory.read(addr)
+    }
+
+    pub fn write_memory(&mut self, addr: u16, value: u8) {
+        self.memory.write(addr, value);
+    }
+
+    // Runs a single CPU instruction, interrupt dispatch or DMA stall, returns the M-cycles taken
+    pub fn step(&mut self) -> u8 {
+        self.frame_ready = false;
+
+        // Fast clock cycles per CPU clock cycle at the current speed
+        let clk_ratio = (self.core.current_clk_period() / FAST_CLK_PERIOD) as u8;
+
+        let cpu_cycles = if self.memory.consume_dma_stall() {
+            // The CPU is stalled during VRAM DMA transfers
+            1
+        } else {
+            // Should we move to an interrupt?
+            let attending_interrupt = if let Some(interrupt) = self.memory.next_pending_interrupt() {
+                self.core.attend_interrupt(interrupt, &mut self.memory)
+            } else {
+                false
+            };
+
+            if attending_interrupt {
+                5
+            } else {
+                self.core.run_step(&mut self.memory)
+            }
+        };
+
+        // The timer, serial port and APU are frozen while the clocks are stopped
+        if !self.core.is_stopped() {
+            self.memory.update_timer(cpu_cycles);
+            self.memory.update_serial(cpu_cycles);
+            self.memory.update_apu(cpu_cycles);
         }
+
+        self.memory.update_dma(cpu_cycles);
+
+        // The display runs one dot each two fast clock cycles, regardless of the CPU speed
+        let dots = cpu_cycles as u32 * 4 * clk_ratio as u32 / 2;
+
+        for _ in 0..dots {
+            self.ppu.update(&mut self.memory);
+            self.frame_dots += 1;
+
+            if self.ppu.is_frame_ready() {
+                self.frame_ready = true;
+                self.frame_dots = 0;
+            }
+        }
+
+        cpu_cycles
     }
 
-    pub fn run(&mut self) {
-        let mut fast_clock_timer = Instant::now();
-        let mut waiting_cpu_cycles = 0u8;
-        let mut waiting_display_cycles = 0u8;
+    // Runs until the PPU completes a frame, or a frame's worth of time with the LCD off
+    pub fn run_frame(&mut self) {
+        loop {
+            self.step();
 
-        'main_loop: loop {
-            // Check input events
-            for event in self.sdl_event_pump.poll_iter() {
-                match event {
-                    Event::Quit { .. } => break 'main_loop,
-                    _ => {}
-                }
+            if self.frame_ready {
+                break;
             }
 
-            // Fast clock cycle timing
-            if fast_clock_timer.elapsed().as_nanos() >= FAST_CLK_PERIOD {
-                fast_clock_timer = Instant::now();
-
-                if waiting_cpu_cycles == 0 {
-                    // Should we move to an interrupt?
-                    let attending_interrupt = if let Some(interrupt) = self.memory.next_pending_interrupt() {
-                        self.core.attend_interrupt(interrupt, &mut self.memory)
-                    } else {
-                        false
-                    };
-
-                    waiting_cpu_cycles = if attending_interrupt {
-                        20
-                    } else {
-                        self.core.run_step(&mut self.memory) * 4
-                    };
-                }
-
-                // Update display each two fast cock cycles
-                if waiting_display_cycles == 0 {
-                    self.display.update(&self.memory);
-
-                    waiting_display_cycles = 2;
-                }
-
-                waiting_cpu_cycles -= 1;
-                waiting_display_cycles -= 1;
+            if self.frame_dots >= DOTS_PER_FRAME {
+                self.frame_dots = 0;
+                break;
             }
         }
     }
 
+    // Runs at least the given M-cycles, instructions aren't split so it may overshoot.
+    // Returns the M-cycles actually run
+    pub fn run_cycles(&mut self, cycles: u32) -> u32 {
+        let mut elapsed = 0;
+
+        while elapsed < cycles {
+            elapsed += self.step() as u32;
+        }
+
+        elapsed
+    }
+
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROM_SIZE: usize = 0x8000;
+    const ENTRY_POINT: usize = 0x0100;
+
+    // Code right after the header
+    const CODE_START: usize = 0x0150;
+
+    const STAT_ADDR: u16 = 0xFF41;
+    const LY_ADDR: u16 = 0xFF44;
+
+    // ROM only cartridge jumping from the entry point to the given code
+    fn test_rom(code: &[u8]) -> Vec<u8> {
+        let mut rom = vec![0; ROM_SIZE];
+
+        rom[ENTRY_POINT..ENTRY_POINT + 3].copy_from_slice(&[0xC3, CODE_START as u8, (CODE_START >> 8) as u8]);
+        rom[CODE_START..CODE_START + code.len()].copy_from_slice(code);
+
+        rom
+    }
+
+    // JP to the instruction itself
+    fn jp_self(addr: usize) -> [u8; 3] {
+        [0xC3, addr as u8, (addr >> 8) as u8]
+    }
+
+    #[test]
+    fn run_frame_renders_background_and_stops_in_vblank() {
+        let mut machine = Machine::from_rom(Model::Dmg, test_rom(&jp_self(CODE_START))).unwrap();
+
+        // Tile 0 becomes solid color 3 and fills the whole background map
+        for addr in 0x8000..0x8010 {
+            machine.write_memory(addr, 0xFF);
+        }
+
+        for addr in 0x9800..0x9C00 {
+            machine.write_memory(addr, 0x00);
+        }
+
+        machine.run_frame();
+
+        let framebuffer = machine.framebuffer();
+        let first_pixel = &framebuffer[..3];
+
+        assert_ne!(first_pixel, [0xFF; 3]);
+        assert!(framebuffer.chunks(3).all(|pixel| pixel == first_pixel));
+
+        assert_eq!(machine.read_memory(LY_ADDR), LCD_HEIGHT as u8);
+        assert_eq!(machine.read_memory(STAT_ADDR) & 0x03, 0x01);
+    }
+
+    #[test]
+    fn vblank_interrupt_is_dispatched_once_per_frame() {
+        let code = [
+            0xF3,             // DI
+            0xAF,             // XOR A
+            0xE0, 0x0F,       // LDH (IF), A
+            0x3E, 0x01,       // LD A, 0x01
+            0xE0, 0xFF,       // LDH (IE), A
+            0xFB              // EI
+        ];
+
+        let mut rom = test_rom(&code);
+        rom[CODE_START + code.len()..][..3].copy_from_slice(&jp_self(CODE_START + code.len()));
+
+        // Handler counting the interrupts at 0xC000
+        rom[0x0040..0x0048].copy_from_slice(&[
+            0xFA, 0x00, 0xC0, // LD A, (0xC000)
+            0x3C,             // INC A
+            0xEA, 0x00, 0xC0, // LD (0xC000), A
+            0xD9              // RETI
+        ]);
+
+        let mut machine = Machine::from_rom(Model::Cgb, rom).unwrap();
+        machine.write_memory(0xC000, 0x00);
+
+        for _ in 0..3 {
+            machine.run_frame();
+        }
+
+        // Let the last dispatch go through
+        machine.run_cycles(100);
+
+        assert_eq!(machine.read_memory(0xC000), 3);
+    }
+
+    #[test]
+    fn halt_bug_reads_next_byte_twice() {
+        let code = [
+            0xF3,             // DI
+            0x3E, 0x01,       // LD A, 0x01
+            0xE0, 0xFF,       // LDH (IE), A
+            0xE0, 0x0F,       // LDH (IF), A
+            0x76,             // HALT, exits at once without dispatching
+            0x06, 0x00,       // LD B, 0x00 runs as LD B, 0x06 then NOP
+            0x78,             // LD A, B
+            0xEA, 0x00, 0xC0  // LD (0xC000), A
+        ];
+
+        let mut rom = test_rom(&code);
+        rom[CODE_START + code.len()..][..3].copy_from_slice(&jp_self(CODE_START + code.len()));
+
+        let mut machine = Machine::from_rom(Model::Cgb, rom).unwrap();
+        machine.run_cycles(100);
+
+        assert_eq!(machine.read_memory(0xC000), 0x06);
+    }
+
+}
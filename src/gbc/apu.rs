@@ -0,0 +1,248 @@
+mod envelope;
+mod length;
+mod square;
+mod wave;
+mod noise;
+
+use square::SquareChannel;
+use wave::WaveChannel;
+use noise::NoiseChannel;
+
+// T-cycles per second, the APU runs at the same rate in double speed
+const APU_FREQUENCY: u32 = 4_194_304;
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+// Samples nobody consumed are dropped past this point (1 s of stereo audio)
+const MAX_BUFFERED_SAMPLES: usize = DEFAULT_SAMPLE_RATE as usize * 2;
+
+// Sound registers
+const NR10_ADDR: usize = 0xFF10;
+const NR14_ADDR: usize = 0xFF14;
+const NR21_ADDR: usize = 0xFF16;
+const NR24_ADDR: usize = 0xFF19;
+const NR30_ADDR: usize = 0xFF1A;
+const NR34_ADDR: usize = 0xFF1E;
+const NR41_ADDR: usize = 0xFF20;
+const NR44_ADDR: usize = 0xFF23;
+const NR50_ADDR: usize = 0xFF24;
+const NR51_ADDR: usize = 0xFF25;
+const NR52_ADDR: usize = 0xFF26;
+
+const WAVE_RAM_START: usize = 0xFF30;
+const WAVE_RAM_END: usize = 0xFF3F;
+
+pub struct Apu {
+    channel1: SquareChannel,
+    channel2: SquareChannel,
+    channel3: WaveChannel,
+    channel4: NoiseChannel,
+
+    // NR50, NR51 and NR52 bit 7
+    master_volume: u8,
+    panning: u8,
+    powered: bool,
+
+    frame_sequencer_step: u8,
+
+    sample_rate: u32,
+    sample_counter: u32,
+
+    // Interleaved left and right samples
+    samples: Vec<f32>
+}
+
+impl Apu {
+
+    pub fn new() -> Self {
+        Self {
+            channel1: SquareChannel::new(true),
+            channel2: SquareChannel::new(false),
+            channel3: WaveChannel::new(),
+            channel4: NoiseChannel::new(),
+            master_volume: 0,
+            panning: 0,
+            powered: false,
+            frame_sequencer_step: 0,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            sample_counter: 0,
+            samples: Vec::new()
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.sample_counter = 0;
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            NR10_ADDR..=NR14_ADDR => self.channel1.read(addr - NR10_ADDR),
+            NR21_ADDR..=NR24_ADDR => self.channel2.read(addr - NR21_ADDR + 1),
+            NR30_ADDR..=NR34_ADDR => self.channel3.read(addr - NR30_ADDR),
+            NR41_ADDR..=NR44_ADDR => self.channel4.read(addr - NR41_ADDR + 1),
+            NR50_ADDR => self.master_volume,
+            NR51_ADDR => self.panning,
+            NR52_ADDR => {
+                let powered = if self.powered { 0x80 } else { 0x00 };
+
+                let channels = [
+                    self.channel1.is_enabled(),
+                    self.channel2.is_enabled(),
+                    self.channel3.is_enabled(),
+                    self.channel4.is_enabled()
+                ];
+
+                let status = channels.iter()
+                    .enumerate()
+                    .fold(0x00, |status, (i, &enabled)| status | ((enabled as u8) << i));
+
+                powered | 0x70 | status
+            },
+            WAVE_RAM_START..=WAVE_RAM_END => self.channel3.read_wave_ram(addr - WAVE_RAM_START),
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        // Wave RAM is always accessible
+        if (WAVE_RAM_START..=WAVE_RAM_END).contains(&addr) {
+            return self.channel3.write_wave_ram(addr - WAVE_RAM_START, value);
+        }
+
+        if addr == NR52_ADDR {
+            let powered = (value & 0x80) != 0;
+
+            if self.powered && !powered {
+                self.power_off();
+            } else if !self.powered && powered {
+                self.frame_sequencer_step = 0;
+            }
+
+            self.powered = powered;
+
+            return;
+        }
+
+        // The rest of the registers are read only while powered off
+        if !self.powered {
+            return;
+        }
+
+        match addr {
+            NR10_ADDR..=NR14_ADDR => self.channel1.write(addr - NR10_ADDR, value),
+            NR21_ADDR..=NR24_ADDR => self.channel2.write(addr - NR21_ADDR + 1, value),
+            NR30_ADDR..=NR34_ADDR => self.channel3.write(addr - NR30_ADDR, value),
+            NR41_ADDR..=NR44_ADDR => self.channel4.write(addr - NR41_ADDR + 1, value),
+            NR50_ADDR => self.master_volume = value,
+            NR51_ADDR => self.panning = value,
+            _ => {}
+        }
+    }
+
+    fn power_off(&mut self) {
+        self.channel1 = SquareChannel::new(true);
+        self.channel2 = SquareChannel::new(false);
+        self.channel3.reset();
+        self.channel4 = NoiseChannel::new();
+
+        self.master_volume = 0;
+        self.panning = 0;
+    }
+
+    // Interleaved stereo samples generated since the last call
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    pub fn update(&mut self, t_cycles: u16, frame_sequencer_steps: u8) {
+        // Silence is still produced while powered off so the output keeps its pace
+        if self.powered {
+            for _ in 0..frame_sequencer_steps {
+                self.step_frame_sequencer();
+            }
+        }
+
+        for _ in 0..t_cycles {
+            if self.powered {
+                self.channel1.tick();
+                self.channel2.tick();
+                self.channel3.tick();
+                self.channel4.tick();
+            }
+
+            // Nearest sample at the output rate
+            self.sample_counter += self.sample_rate;
+
+            if self.sample_counter >= APU_FREQUENCY {
+                self.sample_counter -= APU_FREQUENCY;
+                self.push_sample();
+            }
+        }
+    }
+
+    // 512 Hz, length at 256 Hz, sweep at 128 Hz and envelope at 64 Hz
+    fn step_frame_sequencer(&mut self) {
+        if self.frame_sequencer_step.is_multiple_of(2) {
+            self.channel1.clock_length();
+            self.channel2.clock_length();
+            self.channel3.clock_length();
+            self.channel4.clock_length();
+        }
+
+        if self.frame_sequencer_step == 2 || self.frame_sequencer_step == 6 {
+            self.channel1.clock_sweep();
+        }
+
+        if self.frame_sequencer_step == 7 {
+            self.channel1.clock_envelope();
+            self.channel2.clock_envelope();
+            self.channel4.clock_envelope();
+        }
+
+        self.frame_sequencer_step = (self.frame_sequencer_step + 1) % 8;
+    }
+
+    fn push_sample(&mut self) {
+        if self.samples.len() >= MAX_BUFFERED_SAMPLES {
+            return;
+        }
+
+        let channels = [
+            dac_output(self.channel1.dac_enabled(), self.channel1.output()),
+            dac_output(self.channel2.dac_enabled(), self.channel2.output()),
+            dac_output(self.channel3.dac_enabled(), self.channel3.output()),
+            dac_output(self.channel4.dac_enabled(), self.channel4.output())
+        ];
+
+        // NR51 high nibble routes channels to the left, low nibble to the right
+        let mut left = 0.0;
+        let mut right = 0.0;
+
+        for (i, output) in channels.iter().enumerate() {
+            if (self.panning >> (i + 4)) & 0x01 != 0 {
+                left += output;
+            }
+
+            if (self.panning >> i) & 0x01 != 0 {
+                right += output;
+            }
+        }
+
+        let left_volume = ((self.master_volume >> 4) & 0x07) as f32 + 1.0;
+        let right_volume = (self.master_volume & 0x07) as f32 + 1.0;
+
+        self.samples.push(left / 4.0 * left_volume / 8.0);
+        self.samples.push(right / 4.0 * right_volume / 8.0);
+    }
+
+}
+
+// Digital 0-15 mapped to the analog -1.0..1.0 range, silent with the DAC off
+fn dac_output(dac_enabled: bool, digital: u8) -> f32 {
+    if dac_enabled {
+        1.0 - digital as f32 / 7.5
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,68 @@
+// Volume envelope shared by the square and noise channels (NRx2)
+pub struct Envelope {
+    initial_volume: u8,
+    increase: bool,
+    period: u8,
+
+    volume: u8,
+    timer: u8
+}
+
+impl Envelope {
+
+    pub fn new() -> Self {
+        Self {
+            initial_volume: 0,
+            increase: false,
+            period: 0,
+            volume: 0,
+            timer: 0
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        let increase = if self.increase { 0x08 } else { 0x00 };
+
+        (self.initial_volume << 4) | increase | self.period
+    }
+
+    pub fn write(&mut self, value: u8) {
+        self.initial_volume = value >> 4;
+        self.increase = (value & 0x08) != 0;
+        self.period = value & 0x07;
+    }
+
+    // The DAC is off when the upper 5 bits are cleared
+    pub fn dac_enabled(&self) -> bool {
+        self.initial_volume != 0 || self.increase
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn trigger(&mut self) {
+        self.volume = self.initial_volume;
+        self.timer = self.period;
+    }
+
+    // Clocked by the frame sequencer at 64 Hz
+    pub fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+
+        if self.timer == 0 {
+            self.timer = self.period;
+
+            if self.increase && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increase && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+
+}
@@ -0,0 +1,48 @@
+// Length counter, disables the channel when it expires
+pub struct LengthCounter {
+    max_length: u16,
+    counter: u16,
+    enabled: bool
+}
+
+impl LengthCounter {
+
+    pub fn new(max_length: u16) -> Self {
+        Self {
+            max_length,
+            counter: 0,
+            enabled: false
+        }
+    }
+
+    // NRx1, the register holds the length already elapsed
+    pub fn load(&mut self, value: u8) {
+        self.counter = self.max_length - value as u16;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn trigger(&mut self) {
+        if self.counter == 0 {
+            self.counter = self.max_length;
+        }
+    }
+
+    // Clocked by the frame sequencer at 256 Hz, returns true when the channel has to be disabled
+    pub fn clock(&mut self) -> bool {
+        if !self.enabled || self.counter == 0 {
+            return false;
+        }
+
+        self.counter -= 1;
+
+        self.counter == 0
+    }
+
+}
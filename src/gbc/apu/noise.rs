@@ -0,0 +1,139 @@
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+
+const NOISE_LENGTH: u16 = 64;
+
+const DIVISORS: [u16; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+pub struct NoiseChannel {
+    envelope: Envelope,
+    length: LengthCounter,
+
+    // NR43
+    clock_shift: u8,
+    short_mode: bool,
+    divisor_code: u8,
+
+    timer: u32,
+    lfsr: u16,
+
+    enabled: bool
+}
+
+impl NoiseChannel {
+
+    pub fn new() -> Self {
+        Self {
+            envelope: Envelope::new(),
+            length: LengthCounter::new(NOISE_LENGTH),
+            clock_shift: 0,
+            short_mode: false,
+            divisor_code: 0,
+            timer: 0,
+            lfsr: 0x7FFF,
+            enabled: false
+        }
+    }
+
+    pub fn read(&self, register: usize) -> u8 {
+        match register {
+            1 => 0xFF,
+            2 => self.envelope.read(),
+            3 => {
+                let short_mode = if self.short_mode { 0x08 } else { 0x00 };
+                (self.clock_shift << 4) | short_mode | self.divisor_code
+            },
+            4 => if self.length.is_enabled() { 0xFF } else { 0xBF },
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, register: usize, value: u8) {
+        match register {
+            1 => self.length.load(value & 0x3F),
+
+            2 => {
+                self.envelope.write(value);
+
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            },
+
+            3 => {
+                self.clock_shift = value >> 4;
+                self.short_mode = (value & 0x08) != 0;
+                self.divisor_code = value & 0x07;
+            },
+
+            4 => {
+                self.length.set_enabled((value & 0x40) != 0);
+
+                if (value & 0x80) != 0 {
+                    self.trigger();
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = self.period();
+        self.lfsr = 0x7FFF;
+    }
+
+    fn period(&self) -> u32 {
+        (DIVISORS[self.divisor_code as usize] as u32) << self.clock_shift
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    // Advances a single T-cycle
+    pub fn tick(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+
+        if self.timer != 0 {
+            return;
+        }
+
+        self.timer = self.period();
+
+        let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 0x01;
+        self.lfsr = (self.lfsr >> 1) | (feedback << 14);
+
+        // 7 bit mode also feeds bit 6
+        if self.short_mode {
+            self.lfsr = (self.lfsr & !0x40) | (feedback << 6);
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.enabled || (self.lfsr & 0x01) != 0 {
+            return 0;
+        }
+
+        self.envelope.volume()
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+}
@@ -0,0 +1,234 @@
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+
+const SQUARE_LENGTH: u16 = 64;
+
+const DUTY_PATTERNS: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 1, 1, 1],
+    [0, 1, 1, 1, 1, 1, 1, 0]
+];
+
+// Frequency sweep, channel 1 only (NR10)
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+
+    timer: u8,
+    shadow_frequency: u16,
+    enabled: bool
+}
+
+impl Sweep {
+
+    fn new() -> Self {
+        Self {
+            period: 0,
+            negate: false,
+            shift: 0,
+            timer: 0,
+            shadow_frequency: 0,
+            enabled: false
+        }
+    }
+
+    // Next frequency, None if it overflows
+    fn next_frequency(&self) -> Option<u16> {
+        let delta = self.shadow_frequency >> self.shift;
+
+        let frequency = if self.negate {
+            self.shadow_frequency - delta
+        } else {
+            self.shadow_frequency + delta
+        };
+
+        if frequency > 0x07FF {
+            None
+        } else {
+            Some(frequency)
+        }
+    }
+
+    fn reload_timer(&mut self) {
+        // A period of 0 is treated as 8
+        self.timer = if self.period == 0 { 8 } else { self.period };
+    }
+
+}
+
+pub struct SquareChannel {
+    sweep: Option<Sweep>,
+    envelope: Envelope,
+    length: LengthCounter,
+
+    duty: u8,
+    duty_step: usize,
+
+    frequency: u16,
+    timer: u16,
+
+    enabled: bool
+}
+
+impl SquareChannel {
+
+    pub fn new(with_sweep: bool) -> Self {
+        Self {
+            sweep: if with_sweep { Some(Sweep::new()) } else { None },
+            envelope: Envelope::new(),
+            length: LengthCounter::new(SQUARE_LENGTH),
+            duty: 0,
+            duty_step: 0,
+            frequency: 0,
+            timer: 0,
+            enabled: false
+        }
+    }
+
+    pub fn read(&self, register: usize) -> u8 {
+        match register {
+            0 => match &self.sweep {
+                Some(sweep) => {
+                    let negate = if sweep.negate { 0x08 } else { 0x00 };
+                    0x80 | (sweep.period << 4) | negate | sweep.shift
+                },
+                None => 0xFF
+            },
+            1 => 0x3F | (self.duty << 6),
+            2 => self.envelope.read(),
+            3 => 0xFF,
+            4 => if self.length.is_enabled() { 0xFF } else { 0xBF },
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, register: usize, value: u8) {
+        match register {
+            0 => if let Some(sweep) = self.sweep.as_mut() {
+                sweep.period = (value >> 4) & 0x07;
+                sweep.negate = (value & 0x08) != 0;
+                sweep.shift = value & 0x07;
+            },
+
+            1 => {
+                self.duty = value >> 6;
+                self.length.load(value & 0x3F);
+            },
+
+            2 => {
+                self.envelope.write(value);
+
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            },
+
+            3 => self.frequency = (self.frequency & 0x0700) | value as u16,
+
+            4 => {
+                self.frequency = (self.frequency & 0x00FF) | ((value as u16 & 0x07) << 8);
+                self.length.set_enabled((value & 0x40) != 0);
+
+                if (value & 0x80) != 0 {
+                    self.trigger();
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = self.period();
+
+        if let Some(sweep) = self.sweep.as_mut() {
+            sweep.shadow_frequency = self.frequency;
+            sweep.reload_timer();
+            sweep.enabled = sweep.period != 0 || sweep.shift != 0;
+
+            // The overflow check is done right away
+            if sweep.shift != 0 && sweep.next_frequency().is_none() {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn period(&self) -> u16 {
+        (0x0800 - self.frequency) * 4
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    // Advances a single T-cycle
+    pub fn tick(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.duty_step = (self.duty_step + 1) % 8;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+
+        DUTY_PATTERNS[self.duty as usize][self.duty_step] * self.envelope.volume()
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_sweep(&mut self) {
+        let Some(sweep) = self.sweep.as_mut() else {
+            return;
+        };
+
+        sweep.timer = sweep.timer.saturating_sub(1);
+
+        if sweep.timer != 0 {
+            return;
+        }
+
+        sweep.reload_timer();
+
+        if !sweep.enabled || sweep.period == 0 {
+            return;
+        }
+
+        match sweep.next_frequency() {
+            Some(frequency) if sweep.shift != 0 => {
+                sweep.shadow_frequency = frequency;
+                self.frequency = frequency;
+
+                // The new frequency is checked again for overflow
+                if sweep.next_frequency().is_none() {
+                    self.enabled = false;
+                }
+            },
+            Some(_) => {},
+            None => self.enabled = false
+        }
+    }
+
+}
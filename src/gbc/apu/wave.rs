@@ -0,0 +1,146 @@
+use super::length::LengthCounter;
+
+const WAVE_LENGTH: u16 = 256;
+
+// 32 4-bit samples
+const WAVE_RAM_SIZE: usize = 16;
+
+pub struct WaveChannel {
+    dac_enabled: bool,
+    length: LengthCounter,
+
+    // Output level as a right shift of the samples
+    volume_code: u8,
+
+    frequency: u16,
+    timer: u16,
+
+    wave_ram: [u8; WAVE_RAM_SIZE],
+    position: usize,
+
+    enabled: bool
+}
+
+impl WaveChannel {
+
+    pub fn new() -> Self {
+        Self {
+            dac_enabled: false,
+            length: LengthCounter::new(WAVE_LENGTH),
+            volume_code: 0,
+            frequency: 0,
+            timer: 0,
+            wave_ram: [0; WAVE_RAM_SIZE],
+            position: 0,
+            enabled: false
+        }
+    }
+
+    // Powering the APU off clears everything but the wave RAM
+    pub fn reset(&mut self) {
+        *self = Self {
+            wave_ram: self.wave_ram,
+            ..Self::new()
+        };
+    }
+
+    pub fn read(&self, register: usize) -> u8 {
+        match register {
+            0 => if self.dac_enabled { 0xFF } else { 0x7F },
+            1 => 0xFF,
+            2 => 0x9F | (self.volume_code << 5),
+            3 => 0xFF,
+            4 => if self.length.is_enabled() { 0xFF } else { 0xBF },
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, register: usize, value: u8) {
+        match register {
+            0 => {
+                self.dac_enabled = (value & 0x80) != 0;
+
+                if !self.dac_enabled {
+                    self.enabled = false;
+                }
+            },
+
+            1 => self.length.load(value),
+
+            2 => self.volume_code = (value >> 5) & 0x03,
+
+            3 => self.frequency = (self.frequency & 0x0700) | value as u16,
+
+            4 => {
+                self.frequency = (self.frequency & 0x00FF) | ((value as u16 & 0x07) << 8);
+                self.length.set_enabled((value & 0x40) != 0);
+
+                if (value & 0x80) != 0 {
+                    self.trigger();
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    pub fn read_wave_ram(&self, offset: usize) -> u8 {
+        self.wave_ram[offset]
+    }
+
+    pub fn write_wave_ram(&mut self, offset: usize, value: u8) {
+        self.wave_ram[offset] = value;
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+
+        self.length.trigger();
+        self.timer = self.period();
+        self.position = 0;
+    }
+
+    fn period(&self) -> u16 {
+        (0x0800 - self.frequency) * 2
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.dac_enabled
+    }
+
+    // Advances a single T-cycle
+    pub fn tick(&mut self) {
+        self.timer = self.timer.saturating_sub(1);
+
+        if self.timer == 0 {
+            self.timer = self.period();
+            self.position = (self.position + 1) % (WAVE_RAM_SIZE * 2);
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+
+        // High nibble first
+        let byte = self.wave_ram[self.position / 2];
+        let sample = if self.position.is_multiple_of(2) { byte >> 4 } else { byte & 0x0F };
+
+        match self.volume_code {
+            0 => 0,
+            code => sample >> (code - 1)
+        }
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+}
@@ -0,0 +1,282 @@
+mod rom_only;
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
+mod rtc;
+mod header;
+
+use rom_only::RomOnly;
+use mbc1::Mbc1;
+use mbc2::Mbc2;
+use mbc3::Mbc3;
+use mbc5::Mbc5;
+
+pub use header::{CartridgeHeader, HeaderError, CgbSupport};
+
+const ROM_BANK_SIZE: usize = 0x4000;
+const RAM_BANK_SIZE: usize = 0x2000;
+
+// Memory bank controller interface, all addresses are CPU addresses
+pub trait Mbc: Send {
+    fn read_rom(&self, rom: &[u8], addr: usize) -> u8;
+    fn write_rom(&mut self, addr: usize, value: u8);
+    fn read_ram(&self, ram: &[u8], addr: usize) -> u8;
+    // Returns true if the byte was stored in RAM
+    fn write_ram(&mut self, ram: &mut [u8], addr: usize, value: u8) -> bool;
+
+    // Real time clock state for the save file, if the cartridge has one
+    fn save_rtc(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    // The RTC state changed since it was last saved
+    fn is_rtc_dirty(&self) -> bool {
+        false
+    }
+
+    fn load_rtc(&mut self, _data: &[u8]) {}
+}
+
+pub struct Cartridge {
+    header: CartridgeHeader,
+
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    mbc: Box<dyn Mbc>,
+
+    // Battery backed RAM and RTC have to be persisted
+    has_battery: bool,
+    ram_dirty: bool
+}
+
+impl Cartridge {
+
+    pub fn new(rom: Vec<u8>) -> Result<Self, HeaderError> {
+        let header = CartridgeHeader::parse(&rom)?;
+        let cartridge_type = header.cartridge_type;
+
+        let mbc: Box<dyn Mbc> = match cartridge_type {
+            0x00 | 0x08 | 0x09 => Box::new(RomOnly::new()),
+            0x01..=0x03 => Box::new(Mbc1::new(&rom)),
+            0x05 | 0x06 => Box::new(Mbc2::new()),
+            0x0F | 0x10 => Box::new(Mbc3::new(true)),
+            0x11..=0x13 => Box::new(Mbc3::new(false)),
+            0x19..=0x1B => Box::new(Mbc5::new(false)),
+            0x1C..=0x1E => Box::new(Mbc5::new(true)),
+            _ => return Err(HeaderError::UnsupportedCartridgeType(cartridge_type))
+        };
+
+        // MBC2 has 512 half bytes of built-in RAM, regardless of the header
+        let ram_size = match cartridge_type {
+            0x05 | 0x06 => mbc2::MBC2_RAM_SIZE,
+            _ => header.ram_size
+        };
+
+        let has_battery = matches!(cartridge_type, 0x03 | 0x06 | 0x09 | 0x0F | 0x10 | 0x13 | 0x1B | 0x1E);
+
+        Ok(Self {
+            header,
+            rom,
+            ram: vec![0; ram_size],
+            mbc,
+            has_battery,
+            ram_dirty: false
+        })
+    }
+
+    pub fn header(&self) -> &CartridgeHeader {
+        &self.header
+    }
+
+    pub fn read_rom(&self, addr: usize) -> u8 {
+        self.mbc.read_rom(&self.rom, addr)
+    }
+
+    pub fn write_rom(&mut self, addr: usize, value: u8) {
+        self.mbc.write_rom(addr, value);
+    }
+
+    pub fn read_ram(&self, addr: usize) -> u8 {
+        self.mbc.read_ram(&self.ram, addr)
+    }
+
+    pub fn write_ram(&mut self, addr: usize, value: u8) {
+        if self.mbc.write_ram(&mut self.ram, addr, value) {
+            self.ram_dirty = true;
+        }
+    }
+
+    pub fn has_battery(&self) -> bool {
+        self.has_battery
+    }
+
+    pub fn is_save_dirty(&self) -> bool {
+        self.ram_dirty || self.mbc.is_rtc_dirty()
+    }
+
+    // RAM contents followed by the RTC trailer, if any
+    pub fn save_data(&mut self) -> Vec<u8> {
+        self.ram_dirty = false;
+
+        let mut data = self.ram.clone();
+
+        if let Some(rtc_data) = self.mbc.save_rtc() {
+            data.extend(rtc_data);
+        }
+
+        data
+    }
+
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        let ram_size = self.ram.len().min(data.len());
+        self.ram[..ram_size].copy_from_slice(&data[..ram_size]);
+
+        if data.len() > self.ram.len() {
+            self.mbc.load_rtc(&data[self.ram.len()..]);
+        }
+    }
+
+}
+
+// Banks beyond the ROM size are mirrored
+fn rom_byte(rom: &[u8], bank: usize, addr: usize) -> u8 {
+    rom[(bank * ROM_BANK_SIZE + (addr & (ROM_BANK_SIZE - 1))) % rom.len()]
+}
+
+fn ram_index(ram: &[u8], bank: usize, addr: usize) -> usize {
+    (bank * RAM_BANK_SIZE + (addr & (RAM_BANK_SIZE - 1))) % ram.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+    const ROM_SIZE_ADDR: usize = 0x0148;
+    const RAM_SIZE_ADDR: usize = 0x0149;
+
+    // Every ROM bank starts with its own number, low byte then high byte
+    fn test_cartridge(cartridge_type: u8, rom_size_code: u8, ram_size_code: u8) -> Cartridge {
+        let banks = 2 << rom_size_code;
+        let mut rom = vec![0; banks * ROM_BANK_SIZE];
+
+        for bank in 0..banks {
+            rom[bank * ROM_BANK_SIZE] = bank as u8;
+            rom[bank * ROM_BANK_SIZE + 1] = (bank >> 8) as u8;
+        }
+
+        rom[CARTRIDGE_TYPE_ADDR] = cartridge_type;
+        rom[ROM_SIZE_ADDR] = rom_size_code;
+        rom[RAM_SIZE_ADDR] = ram_size_code;
+
+        Cartridge::new(rom).unwrap()
+    }
+
+    #[test]
+    fn rejects_unsupported_cartridge_type() {
+        let mut rom = vec![0; 2 * ROM_BANK_SIZE];
+        rom[CARTRIDGE_TYPE_ADDR] = 0xFC;
+
+        assert!(matches!(Cartridge::new(rom), Err(HeaderError::UnsupportedCartridgeType(0xFC))));
+    }
+
+    #[test]
+    fn mbc1_switches_rom_banks() {
+        // 128 banks, 2 MiB
+        let mut cartridge = test_cartridge(0x01, 0x06, 0x00);
+
+        assert_eq!(cartridge.read_rom(0x4000), 1);
+
+        cartridge.write_rom(0x2000, 0x05);
+        assert_eq!(cartridge.read_rom(0x4000), 5);
+
+        // Bank 0 can't be selected in the switchable area
+        cartridge.write_rom(0x2000, 0x00);
+        assert_eq!(cartridge.read_rom(0x4000), 1);
+
+        // BANK2 supplies bits 5-6
+        cartridge.write_rom(0x2000, 0x05);
+        cartridge.write_rom(0x4000, 0x01);
+        assert_eq!(cartridge.read_rom(0x4000), 0x25);
+
+        // The 0x00 check only looks at BANK1, so 0x20 maps 0x21
+        cartridge.write_rom(0x2000, 0x20);
+        assert_eq!(cartridge.read_rom(0x4000), 0x21);
+
+        // Mode 1 also applies BANK2 to the fixed area
+        assert_eq!(cartridge.read_rom(0x0000), 0x00);
+        cartridge.write_rom(0x6000, 0x01);
+        assert_eq!(cartridge.read_rom(0x0000), 0x20);
+    }
+
+    #[test]
+    fn mbc1_banks_ram_in_mode_1() {
+        // 4 RAM banks
+        let mut cartridge = test_cartridge(0x03, 0x01, 0x03);
+
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_rom(0x6000, 0x01);
+        cartridge.write_rom(0x4000, 0x02);
+        cartridge.write_ram(0xA000, 0x42);
+
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+
+        cartridge.write_rom(0x6000, 0x00);
+        assert_eq!(cartridge.read_ram(0xA000), 0x00);
+    }
+
+    #[test]
+    fn mbc5_switches_9_bit_rom_banks() {
+        // 512 banks, 8 MiB
+        let mut cartridge = test_cartridge(0x19, 0x08, 0x00);
+
+        cartridge.write_rom(0x2000, 0x05);
+        assert_eq!((cartridge.read_rom(0x4000), cartridge.read_rom(0x4001)), (0x05, 0x00));
+
+        // The 9th bit comes from its own register
+        cartridge.write_rom(0x3000, 0x01);
+        assert_eq!((cartridge.read_rom(0x4000), cartridge.read_rom(0x4001)), (0x05, 0x01));
+
+        // Bank 0 is selectable on MBC5
+        cartridge.write_rom(0x2000, 0x00);
+        cartridge.write_rom(0x3000, 0x00);
+        assert_eq!(cartridge.read_rom(0x4000), 0x00);
+
+        cartridge.write_rom(0x2000, 0xFF);
+        assert_eq!(cartridge.read_rom(0x4000), 0xFF);
+    }
+
+    #[test]
+    fn mbc5_rumble_bit_doesnt_select_ram() {
+        // Rumble cart with 4 RAM banks
+        let mut cartridge = test_cartridge(0x1E, 0x01, 0x03);
+
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_rom(0x4000, 0x01);
+        cartridge.write_ram(0xA000, 0x42);
+
+        // Motor on, same bank
+        cartridge.write_rom(0x4000, 0x09);
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+    }
+
+    #[test]
+    fn only_stored_ram_writes_dirty_the_save() {
+        let mut cartridge = test_cartridge(0x1B, 0x01, 0x02);
+
+        // RAM disabled
+        cartridge.write_ram(0xA000, 0x42);
+        assert!(!cartridge.is_save_dirty());
+        assert_eq!(cartridge.read_ram(0xA000), 0xFF);
+
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        assert!(cartridge.is_save_dirty());
+
+        let save_data = cartridge.save_data();
+        assert!(!cartridge.is_save_dirty());
+        assert_eq!(save_data[0], 0x42);
+    }
+
+}
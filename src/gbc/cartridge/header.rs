@@ -0,0 +1,311 @@
+use std::fmt;
+use std::error::Error;
+
+// Header layout
+const HEADER_END: usize = 0x014F;
+
+const LOGO_START: usize = 0x0104;
+const LOGO_END: usize = 0x0133;
+const TITLE_START: usize = 0x0134;
+const TITLE_END: usize = 0x0143;
+const MANUFACTURER_START: usize = 0x013F;
+const MANUFACTURER_END: usize = 0x0142;
+const CGB_FLAG_ADDR: usize = 0x0143;
+const NEW_LICENSEE_START: usize = 0x0144;
+const NEW_LICENSEE_END: usize = 0x0145;
+const SGB_FLAG_ADDR: usize = 0x0146;
+const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+const ROM_SIZE_ADDR: usize = 0x0148;
+const RAM_SIZE_ADDR: usize = 0x0149;
+const OLD_LICENSEE_ADDR: usize = 0x014B;
+const VERSION_ADDR: usize = 0x014C;
+const HEADER_CHECKSUM_ADDR: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDR: usize = 0x014E;
+
+// The old licensee code defers to the new one with this value
+const USE_NEW_LICENSEE: u8 = 0x33;
+
+const NINTENDO_LOGO: [u8; LOGO_END - LOGO_START + 1] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E
+];
+
+#[derive(Debug)]
+pub enum HeaderError {
+    Truncated(usize),
+    InvalidRomSize(u8),
+    InvalidRamSize(u8),
+    UnsupportedCartridgeType(u8)
+}
+
+impl fmt::Display for HeaderError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeaderError::Truncated(size) => write!(f, "ROM too small to hold a header ({} bytes)", size),
+            HeaderError::InvalidRomSize(value) => write!(f, "invalid ROM size code {:#04X}", value),
+            HeaderError::InvalidRamSize(value) => write!(f, "invalid RAM size code {:#04X}", value),
+            HeaderError::UnsupportedCartridgeType(value) => write!(f, "unsupported cartridge type {:#04X}", value)
+        }
+    }
+
+}
+
+impl Error for HeaderError {}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CgbSupport {
+    None,
+    Compatible,
+    Only
+}
+
+#[derive(Clone)]
+pub struct CartridgeHeader {
+    pub title: String,
+    pub manufacturer_code: Option<String>,
+    pub cgb_support: CgbSupport,
+    pub sgb_support: bool,
+    pub licensee_code: String,
+    pub cartridge_type: u8,
+    pub rom_size: usize,
+    pub ram_size: usize,
+    pub version: u8,
+
+    // Validation report
+    pub logo_valid: bool,
+    pub header_checksum_valid: bool,
+    pub global_checksum_valid: bool,
+    pub rom_size_valid: bool
+}
+
+impl CartridgeHeader {
+
+    pub fn parse(rom: &[u8]) -> Result<Self, HeaderError> {
+        if rom.len() <= HEADER_END {
+            return Err(HeaderError::Truncated(rom.len()));
+        }
+
+        let cgb_support = match rom[CGB_FLAG_ADDR] {
+            0x80 => CgbSupport::Compatible,
+            0xC0 => CgbSupport::Only,
+            _ => CgbSupport::None
+        };
+
+        // CGB titles are shorter and may be followed by a manufacturer code
+        let manufacturer = &rom[MANUFACTURER_START..=MANUFACTURER_END];
+        let has_manufacturer_code = cgb_support != CgbSupport::None
+            && manufacturer.iter().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+
+        let title_end = if has_manufacturer_code {
+            MANUFACTURER_START
+        } else if cgb_support != CgbSupport::None {
+            CGB_FLAG_ADDR
+        } else {
+            TITLE_END + 1
+        };
+
+        let title = ascii_string(&rom[TITLE_START..title_end]);
+
+        let manufacturer_code = if has_manufacturer_code {
+            Some(ascii_string(manufacturer))
+        } else {
+            None
+        };
+
+        let licensee_code = if rom[OLD_LICENSEE_ADDR] == USE_NEW_LICENSEE {
+            ascii_string(&rom[NEW_LICENSEE_START..=NEW_LICENSEE_END])
+        } else {
+            format!("{:02X}", rom[OLD_LICENSEE_ADDR])
+        };
+
+        let rom_size = match rom[ROM_SIZE_ADDR] {
+            value @ 0x00..=0x08 => (32 * 1024) << value,
+            value => return Err(HeaderError::InvalidRomSize(value))
+        };
+
+        let ram_size = match rom[RAM_SIZE_ADDR] {
+            0x00 | 0x01 => 0,
+            0x02 => 8 * 1024,
+            0x03 => 32 * 1024,
+            0x04 => 128 * 1024,
+            0x05 => 64 * 1024,
+            value => return Err(HeaderError::InvalidRamSize(value))
+        };
+
+        Ok(Self {
+            title,
+            manufacturer_code,
+            cgb_support,
+            sgb_support: rom[SGB_FLAG_ADDR] == 0x03,
+            licensee_code,
+            cartridge_type: rom[CARTRIDGE_TYPE_ADDR],
+            rom_size,
+            ram_size,
+            version: rom[VERSION_ADDR],
+            logo_valid: rom[LOGO_START..=LOGO_END] == NINTENDO_LOGO,
+            header_checksum_valid: header_checksum(rom) == rom[HEADER_CHECKSUM_ADDR],
+            global_checksum_valid: global_checksum(rom) == expected_global_checksum(rom),
+            rom_size_valid: rom.len() == rom_size
+        })
+    }
+
+}
+
+impl fmt::Display for CartridgeHeader {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "\t- Title: {}", self.title)?;
+
+        if let Some(manufacturer_code) = &self.manufacturer_code {
+            writeln!(f, "\t- Manufacturer: {}", manufacturer_code)?;
+        }
+
+        writeln!(f, "\t- CGB: {:?}", self.cgb_support)?;
+        writeln!(f, "\t- SGB: {}", self.sgb_support)?;
+        writeln!(f, "\t- Licensee: {}", self.licensee_code)?;
+        writeln!(f, "\t- Type: {:#04X}", self.cartridge_type)?;
+        writeln!(f, "\t- ROM size: {} kB", self.rom_size / 1024)?;
+        writeln!(f, "\t- RAM size: {} kB", self.ram_size / 1024)?;
+        writeln!(f, "\t- Version: {}", self.version)?;
+        writeln!(f, "\t- Logo: {}", validation(self.logo_valid))?;
+        writeln!(f, "\t- Header checksum: {}", validation(self.header_checksum_valid))?;
+        writeln!(f, "\t- Global checksum: {}", validation(self.global_checksum_valid))?;
+        write!(f, "\t- ROM size check: {}", validation(self.rom_size_valid))
+    }
+
+}
+
+fn validation(valid: bool) -> &'static str {
+    if valid { "OK" } else { "MISMATCH" }
+}
+
+// Title bytes are padded with zeros
+fn ascii_string(bytes: &[u8]) -> String {
+    bytes.iter()
+        .take_while(|&&c| c != 0x00)
+        .map(|&c| if c.is_ascii_graphic() || c == b' ' { c as char } else { '?' })
+        .collect()
+}
+
+fn header_checksum(rom: &[u8]) -> u8 {
+    rom[TITLE_START..=VERSION_ADDR].iter()
+        .fold(0u8, |checksum, &byte| checksum.wrapping_sub(byte).wrapping_sub(1))
+}
+
+// Sum of every byte in the ROM but the checksum itself
+fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|(addr, _)| *addr != GLOBAL_CHECKSUM_ADDR && *addr != GLOBAL_CHECKSUM_ADDR + 1)
+        .fold(0u16, |checksum, (_, &byte)| checksum.wrapping_add(byte as u16))
+}
+
+fn expected_global_checksum(rom: &[u8]) -> u16 {
+    ((rom[GLOBAL_CHECKSUM_ADDR] as u16) << 8) | rom[GLOBAL_CHECKSUM_ADDR + 1] as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROM_SIZE: usize = 32 * 1024;
+
+    // 32 KiB ROM with a well formed header
+    fn test_rom(title: &[u8], cgb_flag: u8) -> Vec<u8> {
+        let mut rom = vec![0; ROM_SIZE];
+
+        rom[LOGO_START..=LOGO_END].copy_from_slice(&NINTENDO_LOGO);
+        rom[TITLE_START..TITLE_START + title.len()].copy_from_slice(title);
+        rom[CGB_FLAG_ADDR] = cgb_flag;
+        rom[CARTRIDGE_TYPE_ADDR] = 0x03;
+        rom[RAM_SIZE_ADDR] = 0x03;
+        rom[OLD_LICENSEE_ADDR] = 0x01;
+
+        fix_checksums(&mut rom);
+
+        rom
+    }
+
+    fn fix_checksums(rom: &mut [u8]) {
+        rom[HEADER_CHECKSUM_ADDR] = header_checksum(rom);
+        let global_checksum = global_checksum(rom);
+        rom[GLOBAL_CHECKSUM_ADDR..=GLOBAL_CHECKSUM_ADDR + 1].copy_from_slice(&global_checksum.to_be_bytes());
+    }
+
+    #[test]
+    fn parses_dmg_header() {
+        let header = CartridgeHeader::parse(&test_rom(b"TEST GAME", 0x00)).unwrap();
+
+        assert_eq!(header.title, "TEST GAME");
+        assert_eq!(header.manufacturer_code, None);
+        assert_eq!(header.cgb_support, CgbSupport::None);
+        assert_eq!(header.licensee_code, "01");
+        assert_eq!(header.cartridge_type, 0x03);
+        assert_eq!(header.rom_size, ROM_SIZE);
+        assert_eq!(header.ram_size, 32 * 1024);
+
+        assert!(header.logo_valid);
+        assert!(header.header_checksum_valid);
+        assert!(header.global_checksum_valid);
+        assert!(header.rom_size_valid);
+    }
+
+    #[test]
+    fn parses_cgb_title_and_manufacturer() {
+        let mut rom = test_rom(b"CGBGAME", 0xC0);
+        rom[MANUFACTURER_START..=MANUFACTURER_END].copy_from_slice(b"ABCD");
+
+        let header = CartridgeHeader::parse(&rom).unwrap();
+
+        assert_eq!(header.title, "CGBGAME");
+        assert_eq!(header.manufacturer_code.as_deref(), Some("ABCD"));
+        assert_eq!(header.cgb_support, CgbSupport::Only);
+    }
+
+    #[test]
+    fn reports_checksum_mismatches() {
+        let mut rom = test_rom(b"TEST GAME", 0x00);
+        rom[TITLE_START] = b'B';
+
+        let header = CartridgeHeader::parse(&rom).unwrap();
+
+        assert!(!header.header_checksum_valid);
+        assert!(!header.global_checksum_valid);
+
+        // A byte outside the header only breaks the global checksum
+        let mut rom = test_rom(b"TEST GAME", 0x00);
+        rom[0x4000] = 0xFF;
+
+        let header = CartridgeHeader::parse(&rom).unwrap();
+
+        assert!(header.header_checksum_valid);
+        assert!(!header.global_checksum_valid);
+    }
+
+    #[test]
+    fn reports_rom_size_mismatch() {
+        let mut rom = test_rom(b"TEST GAME", 0x00);
+        rom[ROM_SIZE_ADDR] = 0x01;
+
+        assert!(!CartridgeHeader::parse(&rom).unwrap().rom_size_valid);
+    }
+
+    #[test]
+    fn rejects_truncated_rom() {
+        assert!(matches!(CartridgeHeader::parse(&[0; 0x100]), Err(HeaderError::Truncated(0x100))));
+    }
+
+    #[test]
+    fn rejects_invalid_size_codes() {
+        let mut rom = test_rom(b"TEST GAME", 0x00);
+        rom[ROM_SIZE_ADDR] = 0x09;
+        assert!(matches!(CartridgeHeader::parse(&rom), Err(HeaderError::InvalidRomSize(0x09))));
+
+        let mut rom = test_rom(b"TEST GAME", 0x00);
+        rom[RAM_SIZE_ADDR] = 0x06;
+        assert!(matches!(CartridgeHeader::parse(&rom), Err(HeaderError::InvalidRamSize(0x06))));
+    }
+
+}
@@ -0,0 +1,113 @@
+use super::{Mbc, rom_byte, ram_index, ROM_BANK_SIZE};
+
+// Multicarts are 1 MiB ROMs with a game every 16 banks
+const MULTICART_ROM_SIZE: usize = 64 * ROM_BANK_SIZE;
+const MULTICART_GAME_BANKS: usize = 0x10;
+
+const LOGO_START: usize = 0x0104;
+const LOGO_END: usize = 0x0133;
+
+pub struct Mbc1 {
+    ram_enabled: bool,
+
+    // 5 bit lower ROM bank and 2 bit upper ROM/RAM bank
+    bank1: u8,
+    bank2: u8,
+
+    // Mode 1 also applies BANK2 to the 0x0000-0x3FFF area and RAM
+    advanced_mode: bool,
+
+    // Multicarts only wire 4 bits of BANK1
+    multicart: bool
+}
+
+impl Mbc1 {
+
+    pub fn new(rom: &[u8]) -> Self {
+        Self {
+            ram_enabled: false,
+            bank1: 1,
+            bank2: 0,
+            advanced_mode: false,
+            multicart: is_multicart(rom)
+        }
+    }
+
+    fn bank1_bits(&self) -> usize {
+        if self.multicart { 4 } else { 5 }
+    }
+
+}
+
+impl Mbc for Mbc1 {
+
+    fn read_rom(&self, rom: &[u8], addr: usize) -> u8 {
+        let upper_bank = (self.bank2 as usize) << self.bank1_bits();
+
+        let bank = if addr < ROM_BANK_SIZE {
+            if self.advanced_mode { upper_bank } else { 0 }
+        } else {
+            let lower_bank = self.bank1 as usize & ((1 << self.bank1_bits()) - 1);
+            upper_bank | lower_bank
+        };
+
+        rom_byte(rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
+
+            0x2000..=0x3FFF => {
+                // Bank 0 is mapped as 1, checking all 5 bits even on multicarts
+                self.bank1 = value & 0x1F;
+                if self.bank1 == 0 {
+                    self.bank1 = 1;
+                }
+            },
+
+            0x4000..=0x5FFF => self.bank2 = value & 0x03,
+
+            0x6000..=0x7FFF => self.advanced_mode = (value & 0x01) != 0,
+
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: usize) -> u8 {
+        if !self.ram_enabled || ram.is_empty() {
+            return 0xFF;
+        }
+
+        let bank = if self.advanced_mode { self.bank2 as usize } else { 0 };
+
+        ram[ram_index(ram, bank, addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: usize, value: u8) -> bool {
+        if !self.ram_enabled || ram.is_empty() {
+            return false;
+        }
+
+        let bank = if self.advanced_mode { self.bank2 as usize } else { 0 };
+
+        ram[ram_index(ram, bank, addr)] = value;
+
+        true
+    }
+
+}
+
+// Multicarts repeat the Nintendo logo in the header of each game
+fn is_multicart(rom: &[u8]) -> bool {
+    if rom.len() != MULTICART_ROM_SIZE {
+        return false;
+    }
+
+    let logo = &rom[LOGO_START..=LOGO_END];
+
+    (1..4).filter(|game| {
+        let game_start = game * MULTICART_GAME_BANKS * ROM_BANK_SIZE;
+        &rom[game_start + LOGO_START..=game_start + LOGO_END] == logo
+    }).count() >= 2
+}
@@ -0,0 +1,63 @@
+use super::{Mbc, rom_byte, ROM_BANK_SIZE};
+
+// 512 half bytes of built-in RAM, mirrored through the whole RAM area
+pub const MBC2_RAM_SIZE: usize = 0x200;
+
+pub struct Mbc2 {
+    ram_enabled: bool,
+    rom_bank: u8
+}
+
+impl Mbc2 {
+
+    pub fn new() -> Self {
+        Self {
+            ram_enabled: false,
+            rom_bank: 1
+        }
+    }
+
+}
+
+impl Mbc for Mbc2 {
+
+    fn read_rom(&self, rom: &[u8], addr: usize) -> u8 {
+        let bank = if addr < ROM_BANK_SIZE { 0 } else { self.rom_bank as usize };
+
+        rom_byte(rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        // Bit 8 of the address selects the register
+        if addr < ROM_BANK_SIZE {
+            if (addr & 0x0100) == 0 {
+                self.ram_enabled = (value & 0x0F) == 0x0A;
+            } else {
+                self.rom_bank = value & 0x0F;
+                if self.rom_bank == 0 {
+                    self.rom_bank = 1;
+                }
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: usize) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        // Only the lower nibble is stored
+        0xF0 | ram[addr & (MBC2_RAM_SIZE - 1)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: usize, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+
+        ram[addr & (MBC2_RAM_SIZE - 1)] = value & 0x0F;
+
+        true
+    }
+
+}
@@ -0,0 +1,110 @@
+use super::{Mbc, rom_byte, ram_index, ROM_BANK_SIZE};
+use super::rtc::{Rtc, RTC_SECONDS, RTC_DAYS_HIGH};
+
+pub struct Mbc3 {
+    ram_enabled: bool,
+    rom_bank: u8,
+
+    // RAM bank or RTC register selection
+    ram_bank: u8,
+
+    rtc: Option<Rtc>
+}
+
+impl Mbc3 {
+
+    pub fn new(has_rtc: bool) -> Self {
+        Self {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            rtc: if has_rtc { Some(Rtc::new()) } else { None }
+        }
+    }
+
+    fn rtc_selected(&self) -> bool {
+        (RTC_SECONDS..=RTC_DAYS_HIGH).contains(&self.ram_bank)
+    }
+
+}
+
+impl Mbc for Mbc3 {
+
+    fn read_rom(&self, rom: &[u8], addr: usize) -> u8 {
+        let bank = if addr < ROM_BANK_SIZE { 0 } else { self.rom_bank as usize };
+
+        rom_byte(rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
+
+            0x2000..=0x3FFF => {
+                self.rom_bank = value & 0x7F;
+                if self.rom_bank == 0 {
+                    self.rom_bank = 1;
+                }
+            },
+
+            0x4000..=0x5FFF => self.ram_bank = value,
+
+            0x6000..=0x7FFF => {
+                if let Some(rtc) = self.rtc.as_mut() {
+                    rtc.write_latch(value);
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: usize) -> u8 {
+        if self.ram_enabled && self.rtc_selected() {
+            return match self.rtc.as_ref() {
+                Some(rtc) => rtc.read(self.ram_bank),
+                None => 0xFF
+            };
+        }
+
+        if !self.ram_enabled || ram.is_empty() || self.ram_bank > 0x07 {
+            return 0xFF;
+        }
+
+        ram[ram_index(ram, self.ram_bank as usize, addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: usize, value: u8) -> bool {
+        // The RTC keeps track of its own changes
+        if self.ram_enabled && self.rtc_selected() {
+            if let Some(rtc) = self.rtc.as_mut() {
+                rtc.write(self.ram_bank, value);
+            }
+
+            return false;
+        }
+
+        if !self.ram_enabled || ram.is_empty() || self.ram_bank > 0x07 {
+            return false;
+        }
+
+        ram[ram_index(ram, self.ram_bank as usize, addr)] = value;
+
+        true
+    }
+
+    fn save_rtc(&mut self) -> Option<Vec<u8>> {
+        self.rtc.as_mut().map(|rtc| rtc.save())
+    }
+
+    fn is_rtc_dirty(&self) -> bool {
+        self.rtc.as_ref().is_some_and(|rtc| rtc.is_dirty())
+    }
+
+    fn load_rtc(&mut self, data: &[u8]) {
+        if let Some(rtc) = self.rtc.as_mut() {
+            rtc.load(data);
+        }
+    }
+
+}
@@ -0,0 +1,66 @@
+use super::{Mbc, rom_byte, ram_index, ROM_BANK_SIZE};
+
+pub struct Mbc5 {
+    // On rumble carts bit 3 of the RAM bank register drives the motor instead
+    rumble: bool,
+
+    ram_enabled: bool,
+
+    // 9 bit ROM bank, bank 0 can be mapped to 0x4000-0x7FFF
+    rom_bank: u16,
+    ram_bank: u8
+}
+
+impl Mbc5 {
+
+    pub fn new(rumble: bool) -> Self {
+        Self {
+            rumble,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0
+        }
+    }
+
+}
+
+impl Mbc for Mbc5 {
+
+    fn read_rom(&self, rom: &[u8], addr: usize) -> u8 {
+        let bank = if addr < ROM_BANK_SIZE { 0 } else { self.rom_bank as usize };
+
+        rom_byte(rom, bank, addr)
+    }
+
+    fn write_rom(&mut self, addr: usize, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0x0FF) | (((value & 0x01) as u16) << 8),
+            0x4000..=0x5FFF => {
+                let mask = if self.rumble { 0x07 } else { 0x0F };
+                self.ram_bank = value & mask;
+            },
+            _ => {}
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: usize) -> u8 {
+        if !self.ram_enabled || ram.is_empty() {
+            return 0xFF;
+        }
+
+        ram[ram_index(ram, self.ram_bank as usize, addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: usize, value: u8) -> bool {
+        if !self.ram_enabled || ram.is_empty() {
+            return false;
+        }
+
+        ram[ram_index(ram, self.ram_bank as usize, addr)] = value;
+
+        true
+    }
+
+}
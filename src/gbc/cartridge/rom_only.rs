@@ -0,0 +1,40 @@
+use super::{Mbc, rom_byte, ram_index};
+
+// 32 KiB of ROM and optionally up to 8 KiB of RAM, no banking
+pub struct RomOnly;
+
+impl RomOnly {
+
+    pub fn new() -> Self {
+        Self
+    }
+
+}
+
+impl Mbc for RomOnly {
+
+    fn read_rom(&self, rom: &[u8], addr: usize) -> u8 {
+        rom_byte(rom, addr >> 14, addr)
+    }
+
+    fn write_rom(&mut self, _addr: usize, _value: u8) {}
+
+    fn read_ram(&self, ram: &[u8], addr: usize) -> u8 {
+        if ram.is_empty() {
+            return 0xFF;
+        }
+
+        ram[ram_index(ram, 0, addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: usize, value: u8) -> bool {
+        if ram.is_empty() {
+            return false;
+        }
+
+        ram[ram_index(ram, 0, addr)] = value;
+
+        true
+    }
+
+}
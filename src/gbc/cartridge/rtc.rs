@@ -0,0 +1,257 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// RTC registers, selected through the RAM bank register
+pub const RTC_SECONDS: u8 = 0x08;
+pub const RTC_MINUTES: u8 = 0x09;
+pub const RTC_HOURS: u8 = 0x0A;
+pub const RTC_DAYS_LOW: u8 = 0x0B;
+pub const RTC_DAYS_HIGH: u8 = 0x0C;
+
+// Save file trailer: current and latched registers as 32 bit words plus a 64 bit timestamp
+const RTC_SAVE_SIZE: usize = 48;
+
+// Older emulators store a 32 bit timestamp instead
+const RTC_SAVE_SIZE_SHORT: usize = 44;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const MAX_DAYS: u64 = 512;
+
+// Day counter high register flags
+const DAY_MSB_MASK: u8 = 0x01;
+const HALT_MASK: u8 = 0x40;
+const CARRY_MASK: u8 = 0x80;
+
+pub struct Rtc {
+    // Seconds, minutes, hours, days low and days high
+    registers: [u8; 5],
+    latched: [u8; 5],
+
+    // Latching happens when writing 0x00 and then 0x01
+    latch_armed: bool,
+
+    // Wall clock time the registers were last brought up to date (ms)
+    last_update: u64,
+
+    // The registers changed since they were last saved
+    dirty: bool
+}
+
+impl Rtc {
+
+    pub fn new() -> Self {
+        Self {
+            registers: [0; 5],
+            latched: [0; 5],
+            latch_armed: false,
+            last_update: now_millis(),
+            dirty: false
+        }
+    }
+
+    pub fn read(&self, register: u8) -> u8 {
+        self.latched[(register - RTC_SECONDS) as usize] & register_mask(register)
+    }
+
+    pub fn write(&mut self, register: u8, value: u8) {
+        self.update();
+
+        // Writing the seconds also resets the sub-second counter
+        if register == RTC_SECONDS {
+            self.last_update = now_millis();
+        }
+
+        let value = value & register_mask(register);
+
+        self.registers[(register - RTC_SECONDS) as usize] = value;
+        self.latched[(register - RTC_SECONDS) as usize] = value;
+
+        self.dirty = true;
+    }
+
+    pub fn write_latch(&mut self, value: u8) {
+        if self.latch_armed && value == 0x01 {
+            self.update();
+            self.latched = self.registers;
+        }
+
+        self.latch_armed = value == 0x00;
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn save(&mut self) -> Vec<u8> {
+        self.update();
+        self.dirty = false;
+
+        let mut data = Vec::with_capacity(RTC_SAVE_SIZE);
+
+        for register in self.registers.iter().chain(self.latched.iter()) {
+            data.extend_from_slice(&(*register as u32).to_le_bytes());
+        }
+
+        data.extend_from_slice(&(self.last_update / 1000).to_le_bytes());
+
+        data
+    }
+
+    pub fn load(&mut self, data: &[u8]) {
+        if data.len() != RTC_SAVE_SIZE && data.len() != RTC_SAVE_SIZE_SHORT {
+            return;
+        }
+
+        let word = |index: usize| data[index * 4];
+
+        for i in 0..5 {
+            self.registers[i] = word(i) & register_mask(RTC_SECONDS + i as u8);
+            self.latched[i] = word(i + 5) & register_mask(RTC_SECONDS + i as u8);
+        }
+
+        let mut timestamp = [0u8; 8];
+        timestamp[..data.len() - 40].copy_from_slice(&data[40..]);
+
+        // Apply the time elapsed while the emulator was closed
+        self.last_update = u64::from_le_bytes(timestamp) * 1000;
+        self.update();
+    }
+
+    fn update(&mut self) {
+        let now = now_millis();
+
+        if self.registers[4] & HALT_MASK != 0 || now < self.last_update {
+            self.last_update = now;
+            return;
+        }
+
+        let elapsed_seconds = (now - self.last_update) / 1000;
+
+        // Keep the sub-second remainder for the next update
+        self.last_update += elapsed_seconds * 1000;
+
+        if elapsed_seconds != 0 {
+            self.advance(elapsed_seconds);
+        }
+    }
+
+    fn advance(&mut self, seconds: u64) {
+        let days = ((self.registers[4] & DAY_MSB_MASK) as u64) << 8 | self.registers[3] as u64;
+
+        let total = seconds
+            + self.registers[0] as u64
+            + self.registers[1] as u64 * 60
+            + self.registers[2] as u64 * 60 * 60
+            + days * SECONDS_PER_DAY;
+
+        let days = total / SECONDS_PER_DAY;
+
+        self.registers[0] = (total % 60) as u8;
+        self.registers[1] = ((total / 60) % 60) as u8;
+        self.registers[2] = ((total / (60 * 60)) % 24) as u8;
+        self.registers[3] = (days % MAX_DAYS) as u8;
+
+        // The carry flag stays set until cleared by software
+        let mut days_high = self.registers[4] & (HALT_MASK | CARRY_MASK);
+        days_high |= ((days % MAX_DAYS) >> 8) as u8 & DAY_MSB_MASK;
+        if days >= MAX_DAYS {
+            days_high |= CARRY_MASK;
+        }
+
+        self.registers[4] = days_high;
+
+        self.dirty = true;
+    }
+
+}
+
+fn register_mask(register: u8) -> u8 {
+    match register {
+        RTC_SECONDS | RTC_MINUTES => 0x3F,
+        RTC_HOURS => 0x1F,
+        RTC_DAYS_LOW => 0xFF,
+        RTC_DAYS_HIGH => CARRY_MASK | HALT_MASK | DAY_MSB_MASK,
+        _ => panic!("Error selecting RTC register")
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latched(rtc: &mut Rtc) -> [u8; 5] {
+        rtc.write_latch(0x00);
+        rtc.write_latch(0x01);
+
+        [RTC_SECONDS, RTC_MINUTES, RTC_HOURS, RTC_DAYS_LOW, RTC_DAYS_HIGH].map(|register| rtc.read(register))
+    }
+
+    #[test]
+    fn save_round_trip_keeps_registers() {
+        let mut rtc = Rtc::new();
+
+        // Halted so no time passes during the test
+        rtc.write(RTC_DAYS_HIGH, HALT_MASK | DAY_MSB_MASK);
+        rtc.write(RTC_SECONDS, 12);
+        rtc.write(RTC_MINUTES, 34);
+        rtc.write(RTC_HOURS, 5);
+        rtc.write(RTC_DAYS_LOW, 0x67);
+
+        let data = rtc.save();
+        assert_eq!(data.len(), RTC_SAVE_SIZE);
+        assert!(!rtc.is_dirty());
+
+        let mut loaded = Rtc::new();
+        loaded.load(&data);
+
+        assert_eq!(latched(&mut loaded), [12, 34, 5, 0x67, HALT_MASK | DAY_MSB_MASK]);
+    }
+
+    #[test]
+    fn load_applies_elapsed_time() {
+        let mut data = vec![0; RTC_SAVE_SIZE_SHORT];
+        data[0] = 10;
+
+        // Saved a minute and a half ago, with a 32 bit timestamp
+        let timestamp = (now_millis() / 1000 - 90) as u32;
+        data[40..].copy_from_slice(&timestamp.to_le_bytes());
+
+        let mut rtc = Rtc::new();
+        rtc.load(&data);
+
+        let [seconds, minutes, hours, ..] = latched(&mut rtc);
+
+        // A second may tick over while the test runs
+        assert!(seconds == 40 || seconds == 41, "seconds: {}", seconds);
+        assert_eq!((minutes, hours), (1, 0));
+        assert!(rtc.is_dirty());
+    }
+
+    #[test]
+    fn halted_rtc_ignores_elapsed_time() {
+        let mut data = vec![0; RTC_SAVE_SIZE];
+        data[16] = HALT_MASK;
+
+        let timestamp = now_millis() / 1000 - 90;
+        data[40..].copy_from_slice(&timestamp.to_le_bytes());
+
+        let mut rtc = Rtc::new();
+        rtc.load(&data);
+
+        assert_eq!(latched(&mut rtc), [0, 0, 0, 0, HALT_MASK]);
+    }
+
+    #[test]
+    fn day_counter_overflow_sets_carry() {
+        let mut rtc = Rtc::new();
+        rtc.registers = [59, 59, 23, 0xFF, DAY_MSB_MASK];
+
+        rtc.advance(1);
+
+        assert_eq!(rtc.registers, [0, 0, 0, 0, CARRY_MASK]);
+    }
+
+}
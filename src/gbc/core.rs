@@ -2,9 +2,12 @@ mod register_file;
 mod instructions;
 pub mod interrupt;
 
-use register_file::RegisterFile;
+use register_file::{RegisterFile, Reg16};
 use super::memory::Memory;
+use super::model::Model;
+use super::{SLOW_CLK_PERIOD, FAST_CLK_PERIOD};
 use instructions::InstructionInfo;
+use interrupt::Interrupt;
 
 #[derive(Clone, Copy)]
 enum SpeedMode {
@@ -12,6 +15,17 @@ enum SpeedMode {
     Fast
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum PowerMode {
+    Running,
+    Halted,
+    Stopped,
+    SwitchingSpeed(u16)
+}
+
+// M-cycles the CPU is stalled during a speed switch
+const SPEED_SWITCH_CYCLES: u16 = 2050;
+
 pub struct Core {
     reg: RegisterFile,
 
@@ -23,6 +37,9 @@ pub struct Core {
     ime_enabled: bool,
     ime_enable_request: u8,
 
+    power_mode: PowerMode,
+    halt_bug: bool,
+
     speed_mode: SpeedMode
 }
 
@@ -34,19 +51,113 @@ impl Core {
             pc: 0,
             sp: 0,
             prefix_enabled: false,
-            ime_enabled: true,
+            ime_enabled: false,
             ime_enable_request: 0,
+            power_mode: PowerMode::Running,
+            halt_bug: false,
             speed_mode: SpeedMode::Slow
         }
     }
 
-    pub fn current_clk_period(&self) -> SpeedMode {
-        self.speed_mode
+    // CPU state left by the boot ROM
+    pub fn init_post_boot(&mut self, model: Model, dmg_compatibility: bool) {
+        match model {
+            Model::Dmg | Model::Mgb => {
+                // The MGB is told apart by A = 0xFF
+                let a = if model == Model::Mgb { 0xFF } else { 0x01 };
+
+                self.reg.dwrite(Reg16::AF, (a << 8) | 0x00B0);
+                self.reg.dwrite(Reg16::BC, 0x0013);
+                self.reg.dwrite(Reg16::DE, 0x00D8);
+                self.reg.dwrite(Reg16::HL, 0x014D);
+            },
+
+            Model::Cgb | Model::Agb => {
+                // The AGB boot ROM ends with an extra INC B, clearing the flags
+                if model == Model::Agb {
+                    self.reg.dwrite(Reg16::AF, 0x1100);
+                    self.reg.dwrite(Reg16::BC, 0x0100);
+                } else {
+                    self.reg.dwrite(Reg16::AF, 0x1180);
+                    self.reg.dwrite(Reg16::BC, 0x0000);
+                }
+
+                if dmg_compatibility {
+                    self.reg.dwrite(Reg16::DE, 0x0008);
+                    self.reg.dwrite(Reg16::HL, 0x007C);
+                } else {
+                    self.reg.dwrite(Reg16::DE, 0xFF56);
+                    self.reg.dwrite(Reg16::HL, 0x000D);
+                }
+            }
+        }
+
+        self.pc = 0x0100;
+        self.sp = 0xFFFE;
+
+        self.ime_enabled = false;
+        self.ime_enable_request = 0;
+    }
+
+    pub fn current_clk_period(&self) -> u128 {
+        match self.speed_mode {
+            SpeedMode::Slow => SLOW_CLK_PERIOD,
+            SpeedMode::Fast => FAST_CLK_PERIOD
+        }
+    }
+
+    pub fn is_double_speed(&self) -> bool {
+        matches!(self.speed_mode, SpeedMode::Fast)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        matches!(self.power_mode, PowerMode::Stopped | PowerMode::SwitchingSpeed(_))
     }
 
     pub fn run_step(&mut self, memory: &mut Memory) -> u8 {
+        // Low power modes just let time pass until the wake up condition is met
+        match self.power_mode {
+            PowerMode::Halted => {
+                // Any enabled interrupt wakes the CPU up, even if IME is cleared
+                if memory.next_pending_interrupt().is_none() {
+                    return 1;
+                }
+
+                self.power_mode = PowerMode::Running;
+            },
+
+            PowerMode::Stopped => {
+                if !memory.is_interrupt_requested(Interrupt::Joypad) {
+                    return 1;
+                }
+
+                self.power_mode = PowerMode::Running;
+            },
+
+            PowerMode::SwitchingSpeed(remaining_cycles) => {
+                self.power_mode = if remaining_cycles > 1 {
+                    PowerMode::SwitchingSpeed(remaining_cycles - 1)
+                } else {
+                    PowerMode::Running
+                };
+
+                return 1;
+            },
+
+            PowerMode::Running => {}
+        }
+
+        let halt_bug = self.halt_bug;
+        self.halt_bug = false;
+
         let current_instruction = memory.read(self.pc);
 
+        // The PC fails to increment after fetching the byte following HALT,
+        // so the same byte is read again as the first operand
+        if halt_bug {
+            self.pc = self.pc.wrapping_sub(1);
+        }
+
         let instruction_info = self.decode_and_execute(current_instruction, memory);
         let pc_offset = instruction_info.0;
         let clock_cycles = instruction_info.1;
@@ -97,7 +208,7 @@ impl Core {
                                 match (opcode >> 3) & 0x03 {
                                     0x00 => self.nop(),
                                     0x01 => self.ld_imm16_sp(memory),
-                                    0x02 => self.stop(),
+                                    0x02 => self.stop(memory),
                                     0x03 => self.jr_imm8(memory),
                                     _ => panic!("Error decoding instruction (1)")
                                 }
@@ -162,7 +273,7 @@ impl Core {
                 // Block 1
                 0x01 => {
                     if opcode == 0x76 {
-                        self.halt()
+                        self.halt(memory)
                     } else {
                         self.ld_r8_r8(opcode, memory)
                     }
@@ -1,4 +1,4 @@
-use super::Core;
+use super::{Core, PowerMode, SpeedMode, SPEED_SWITCH_CYCLES};
 use super::register_file::{Reg8, Reg16, Flag, map_r8};
 
 use super::Memory;
@@ -452,8 +452,26 @@ impl Core {
         InstructionInfo(2, 2)
     }
 
-    pub fn stop(&self) -> InstructionInfo {
-        InstructionInfo(2, 0)
+    pub fn stop(&mut self, memory: &mut Memory) -> InstructionInfo {
+        // DIV is reset when entering STOP
+        memory.reset_divider();
+
+        if memory.is_speed_switch_armed() {
+            // CGB speed switch, the CPU is stalled until the new clock settles
+            self.speed_mode = match self.speed_mode {
+                SpeedMode::Slow => SpeedMode::Fast,
+                SpeedMode::Fast => SpeedMode::Slow
+            };
+
+            memory.set_double_speed(self.is_double_speed());
+
+            self.power_mode = PowerMode::SwitchingSpeed(SPEED_SWITCH_CYCLES);
+        } else {
+            // Wait for a button press with the clocks stopped
+            self.power_mode = PowerMode::Stopped;
+        }
+
+        InstructionInfo(2, 1)
     }
 
     pub fn ld_r8_r8(&mut self, opcode: u8, memory: &mut Memory) -> InstructionInfo {
@@ -462,7 +480,7 @@ impl Core {
 
         if src_bit_field == 0x06 {
             if dst_bit_field == 0x06 {
-                return self.halt();
+                return self.halt(memory);
             }
 
             let addr = self.reg.dread(Reg16::HL);
@@ -491,8 +509,18 @@ impl Core {
         }
     }
 
-    pub fn halt(&self) -> InstructionInfo {
-        unimplemented!()
+    pub fn halt(&mut self, memory: &Memory) -> InstructionInfo {
+        // A pending EI counts as enabled, as IME is set right after this instruction
+        let ime_enabled = self.ime_enabled || self.ime_enable_request != 0;
+
+        if !ime_enabled && memory.next_pending_interrupt().is_some() {
+            // HALT bug: the CPU doesn't halt and fails to increment PC after the next fetch
+            self.halt_bug = true;
+        } else {
+            self.power_mode = PowerMode::Halted;
+        }
+
+        InstructionInfo(1, 1)
     }
 
     pub fn add_a_r8(&mut self, opcode: u8, memory: &Memory) -> InstructionInfo {
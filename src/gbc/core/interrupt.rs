@@ -1,4 +1,4 @@
-use super::Core;
+use super::{Core, PowerMode};
 
 use super::Memory;
 
@@ -22,10 +22,15 @@ pub enum Interrupt {
 impl Core {
 
     pub fn attend_interrupt(&mut self, interrupt: Interrupt, memory: &mut Memory) -> bool {
-        if self.ime_enabled {
+        // Nothing is dispatched while the clocks are stopped or the speed is switching
+        if self.ime_enabled && !self.is_stopped() {
             self.ime_enabled = false;
             self.ime_enable_request = 0;
 
+            memory.clear_interrupt(interrupt);
+
+            self.power_mode = PowerMode::Running;
+
             let pc_lsb = self.pc & 0x00FF;
             let pc_msb = self.pc >> 8;
 
@@ -0,0 +1,156 @@
+// OAM DMA copies 160 bytes, one per M-cycle
+const OAM_DMA_LENGTH: u16 = 0xA0;
+
+// M-cycles between the write to DMA and the first transfer
+const OAM_DMA_START_DELAY: u8 = 1;
+
+pub struct OamDma {
+    source: u16,
+    progress: u16,
+    start_delay: u8,
+    active: bool
+}
+
+impl OamDma {
+
+    pub fn new() -> Self {
+        Self {
+            source: 0,
+            progress: 0,
+            start_delay: 0,
+            active: false
+        }
+    }
+
+    pub fn start(&mut self, value: u8) {
+        // Restarting an active transfer begins again from the new source
+        self.source = (value as u16) << 8;
+        self.progress = 0;
+        self.start_delay = OAM_DMA_START_DELAY;
+        self.active = true;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active && self.start_delay == 0
+    }
+
+    // Advances a single M-cycle, returning the source address and OAM offset to copy
+    pub fn step(&mut self) -> Option<(u16, usize)> {
+        if !self.active {
+            return None;
+        }
+
+        if self.start_delay != 0 {
+            self.start_delay -= 1;
+            return None;
+        }
+
+        let transfer = (self.source + self.progress, self.progress as usize);
+
+        self.progress += 1;
+        if self.progress == OAM_DMA_LENGTH {
+            self.active = false;
+        }
+
+        Some(transfer)
+    }
+
+}
+
+// VRAM DMA registers
+const HDMA1_ADDR: usize = 0xFF51;
+const HDMA2_ADDR: usize = 0xFF52;
+const HDMA3_ADDR: usize = 0xFF53;
+const HDMA4_ADDR: usize = 0xFF54;
+const HDMA5_ADDR: usize = 0xFF55;
+
+// VRAM DMA copies blocks of 16 bytes
+pub const HDMA_BLOCK_SIZE: u16 = 0x10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum HdmaMode {
+    GeneralPurpose,
+    HBlank
+}
+
+pub struct Hdma {
+    source: u16,
+    destination: u16,
+
+    mode: HdmaMode,
+    remaining_blocks: u8,
+    active: bool
+}
+
+impl Hdma {
+
+    pub fn new() -> Self {
+        Self {
+            source: 0,
+            destination: 0,
+            mode: HdmaMode::GeneralPurpose,
+            remaining_blocks: 0,
+            active: false
+        }
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            HDMA5_ADDR => {
+                // Bit 7 is cleared while a transfer is active, all ones once completed
+                let status = if self.active { 0x00 } else { 0x80 };
+                status | (self.remaining_blocks.wrapping_sub(1) & 0x7F)
+            },
+
+            // Source and destination are write only
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        match addr {
+            HDMA1_ADDR => self.source = (self.source & 0x00FF) | ((value as u16) << 8),
+            HDMA2_ADDR => self.source = (self.source & 0xFF00) | (value & 0xF0) as u16,
+            HDMA3_ADDR => self.destination = (self.destination & 0x00FF) | (((value & 0x1F) as u16) << 8),
+            HDMA4_ADDR => self.destination = (self.destination & 0xFF00) | (value & 0xF0) as u16,
+
+            HDMA5_ADDR => {
+                if self.active && self.mode == HdmaMode::HBlank && (value & 0x80) == 0 {
+                    // Clearing bit 7 cancels the HBlank transfer
+                    self.active = false;
+                } else {
+                    self.mode = if (value & 0x80) == 0 {
+                        HdmaMode::GeneralPurpose
+                    } else {
+                        HdmaMode::HBlank
+                    };
+
+                    self.remaining_blocks = (value & 0x7F) + 1;
+                    self.active = true;
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    // Returns the source and VRAM offset of the next block to copy, if any is due
+    pub fn next_block(&mut self, hblank: bool) -> Option<(u16, u16)> {
+        if !self.active || (self.mode == HdmaMode::HBlank && !hblank) {
+            return None;
+        }
+
+        let block = (self.source, self.destination & 0x1FF0);
+
+        self.source = self.source.wrapping_add(HDMA_BLOCK_SIZE);
+        self.destination = self.destination.wrapping_add(HDMA_BLOCK_SIZE);
+
+        self.remaining_blocks -= 1;
+        if self.remaining_blocks == 0 {
+            self.active = false;
+        }
+
+        Some(block)
+    }
+
+}
@@ -0,0 +1,96 @@
+// P1 select lines, active low
+const SELECT_DIRECTIONS: u8 = 0x10;
+const SELECT_ACTIONS: u8 = 0x20;
+const SELECT_MASK: u8 = SELECT_DIRECTIONS | SELECT_ACTIONS;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start
+}
+
+impl Button {
+
+    pub const ALL: [Button; 8] = [
+        Button::Right, Button::Left, Button::Up, Button::Down,
+        Button::A, Button::B, Button::Select, Button::Start
+    ];
+
+    // Directions in the low nibble, actions in the high one
+    fn mask(self) -> u8 {
+        match self {
+            Button::Right => 0x01,
+            Button::Left => 0x02,
+            Button::Up => 0x04,
+            Button::Down => 0x08,
+            Button::A => 0x10,
+            Button::B => 0x20,
+            Button::Select => 0x40,
+            Button::Start => 0x80
+        }
+    }
+
+}
+
+pub struct Joypad {
+    select: u8,
+    pressed: u8
+}
+
+impl Joypad {
+
+    pub fn new() -> Self {
+        Self {
+            select: SELECT_MASK,
+            pressed: 0x00
+        }
+    }
+
+    // P1/JOYP
+    pub fn read(&self) -> u8 {
+        0xC0 | self.select | (!self.low_lines() & 0x0F)
+    }
+
+    // Returns true if a line went low and the interrupt has to be requested
+    pub fn write(&mut self, value: u8) -> bool {
+        let previous_lines = self.low_lines();
+        self.select = value & SELECT_MASK;
+
+        (self.low_lines() & !previous_lines) != 0
+    }
+
+    // Returns true if a line went low and the interrupt has to be requested
+    pub fn set_button(&mut self, button: Button, pressed: bool) -> bool {
+        let previous_lines = self.low_lines();
+
+        if pressed {
+            self.pressed |= button.mask();
+        } else {
+            self.pressed &= !button.mask();
+        }
+
+        (self.low_lines() & !previous_lines) != 0
+    }
+
+    // Input lines pulled low by pressed buttons in the selected groups
+    fn low_lines(&self) -> u8 {
+        let mut lines = 0x00;
+
+        if (self.select & SELECT_DIRECTIONS) == 0 {
+            lines |= self.pressed & 0x0F;
+        }
+
+        if (self.select & SELECT_ACTIONS) == 0 {
+            lines |= self.pressed >> 4;
+        }
+
+        lines
+    }
+
+}
@@ -1,4 +1,12 @@
 use super::core::interrupt::Interrupt;
+use super::timer::Timer;
+use super::joypad::{Joypad, Button};
+use super::apu::Apu;
+use super::serial::{Serial, SerialLink};
+use super::palette::{self, PaletteMemory};
+use super::dma::{OamDma, Hdma, HDMA_BLOCK_SIZE};
+use super::cartridge::Cartridge;
+use super::model::Model;
 
 // Memory map
 
@@ -14,15 +22,15 @@ const VRAM_START: usize = 0x8000;
 const VRAM_END: usize = 0x9FFF;
 const VRAM_SIZE: usize = VRAM_END - VRAM_START + 1;
 
-const VRAM_CHAR_DATA_START: usize = VRAM_START;
+pub const VRAM_CHAR_DATA_START: usize = VRAM_START;
 const VRAM_CHAR_DATA_END: usize = 0x97FF;
 const VRAM_CHAR_DATA_SIZE: usize = VRAM_CHAR_DATA_END - VRAM_CHAR_DATA_START + 1;
 
-const VRAM_BG_DATA_1_START: usize = VRAM_CHAR_DATA_END + 1;
+pub const VRAM_BG_DATA_1_START: usize = VRAM_CHAR_DATA_END + 1;
 const VRAM_BG_DATA_1_END: usize = 0x9BFF;
 const VRAM_BG_DATA_1_SIZE: usize = VRAM_BG_DATA_1_END - VRAM_BG_DATA_1_START + 1;
 
-const VRAM_BG_DATA_2_START: usize = VRAM_BG_DATA_1_END + 1;
+pub const VRAM_BG_DATA_2_START: usize = VRAM_BG_DATA_1_END + 1;
 const VRAM_BG_DATA_2_END: usize = VRAM_END;
 const VRAM_BG_DATA_2_SIZE: usize = VRAM_BG_DATA_2_END - VRAM_BG_DATA_2_START + 1;
 
@@ -47,7 +55,7 @@ const ECHO_WRAM_END: usize = 0xFDFF;
 const ECHO_WRAM_SIZE: usize = ECHO_WRAM_END - ECHO_WRAM_START + 1;
 
 const OAM_START: usize = 0xFE00;
-const OAM_END: usize = 0xFEBF;
+const OAM_END: usize = 0xFE9F;
 const OAM_SIZE: usize = OAM_END - OAM_START + 1;
 
 const NOT_USABLE_START: usize = 0xFEA0;
@@ -58,70 +66,240 @@ const OTHER_START: usize = 0xFF00;
 const OTHER_END: usize = MEMORY_END;
 const OTHER_SIZE: usize = OTHER_END - OTHER_START + 1;
 
+// Boot ROM is mapped over the cartridge header gap
+const BOOT_ROM_LOW_END: usize = 0x00FF;
+const BOOT_ROM_HIGH_START: usize = 0x0200;
+const BOOT_ROM_HIGH_END: usize = 0x08FF;
+
 // Memory mapped registers
 
+const P1_ADDR: usize = 0xFF00;
+
+const SERIAL_START: usize = 0xFF01;
+const SERIAL_END: usize = 0xFF02;
+
+const TIMER_START: usize = 0xFF04;
+const TIMER_END: usize = 0xFF07;
+
+const APU_START: usize = 0xFF10;
+const APU_END: usize = 0xFF3F;
+
+const HDMA_START: usize = 0xFF51;
+const HDMA_END: usize = 0xFF55;
+
 const IF_ADDR: usize = 0xFF0F;
+const DMA_ADDR: usize = 0xFF46;
 const LCDC_ADDR: usize = 0xFF40;
+const STAT_ADDR: usize = 0xFF41;
 const SCY_ADDR: usize = 0xFF42;
 const SCX_ADDR: usize = 0xFF43;
+const LY_ADDR: usize = 0xFF44;
+const LYC_ADDR: usize = 0xFF45;
+const WY_ADDR: usize = 0xFF4A;
+const WX_ADDR: usize = 0xFF4B;
+const BGP_ADDR: usize = 0xFF47;
+const OBP0_ADDR: usize = 0xFF48;
+const OBP1_ADDR: usize = 0xFF49;
+const KEY0_ADDR: usize = 0xFF4C;
+const KEY1_ADDR: usize = 0xFF4D;
+const BOOT_ADDR: usize = 0xFF50;
 const VBK_ADDR: usize = 0xFF4F;
+const BCPS_ADDR: usize = 0xFF68;
+const BCPD_ADDR: usize = 0xFF69;
+const OCPS_ADDR: usize = 0xFF6A;
+const OCPD_ADDR: usize = 0xFF6B;
+const OPRI_ADDR: usize = 0xFF6C;
 const SVBK_ADDR: usize = 0xFF70;
 const IE_ADDR: usize = 0xFFFF;
 
 pub struct Memory {
+    model: Model,
+
     fixed_memory: [u8; MEMORY_SIZE],
 
+    cartridge: Option<Cartridge>,
+
+    boot_rom: Vec<u8>,
+    boot_rom_mapped: bool,
+
+    // Selected through KEY0, locked once the boot ROM is unmapped
+    dmg_compatibility: bool,
+
     vram_banks: [[u8; VRAM_SIZE]; 2],
     active_vram_bank: usize,
 
     sw_wram_banks: [[u8; SW_WRAM_SIZE]; 7],
-    active_sw_wram_bank: usize
+    active_sw_wram_bank: usize,
+
+    timer: Timer,
+    joypad: Joypad,
+    serial: Serial,
+    apu: Apu,
+
+    bg_palettes: PaletteMemory,
+    obj_palettes: PaletteMemory,
+
+    oam_dma: OamDma,
+
+    hdma: Hdma,
+    dma_stall_cycles: u16
 }
 
 impl Memory {
 
-    pub fn new() -> Self {
-        Self {
+    pub fn new(model: Model) -> Self {
+        let mut memory = Self {
+            model,
+
             fixed_memory: [0; MEMORY_SIZE],
 
+            cartridge: None,
+
+            boot_rom: Vec::new(),
+            boot_rom_mapped: false,
+
+            dmg_compatibility: false,
+
             vram_banks: [[0; VRAM_SIZE]; 2],
             active_vram_bank: 0,
 
             sw_wram_banks: [[0; SW_WRAM_SIZE]; 7],
-            active_sw_wram_bank: 0
+            active_sw_wram_bank: 0,
+
+            timer: Timer::new(),
+            joypad: Joypad::new(),
+            serial: Serial::new(model.is_cgb()),
+            apu: Apu::new(),
+
+            bg_palettes: PaletteMemory::new(),
+            obj_palettes: PaletteMemory::new(),
+
+            oam_dma: OamDma::new(),
+
+            hdma: Hdma::new(),
+            dma_stall_cycles: 0
+        };
+
+        // Monochrome models render their shades through the color palettes
+        if !model.is_cgb() {
+            let shades = if model == Model::Mgb { &palette::MGB_SHADES } else { &palette::DMG_SHADES };
+
+            memory.bg_palettes.load_palette(0, shades);
+            memory.obj_palettes.load_palette(0, shades);
+            memory.obj_palettes.load_palette(1, shades);
         }
+
+        memory
     }
 
     pub fn read(&self, addr: u16) -> u8 {
+        // During OAM DMA the CPU can only reach HRAM and the IO registers
+        if self.oam_dma.is_active() && (addr as usize) < OTHER_START {
+            return 0xFF;
+        }
+
+        self.read_bus(addr)
+    }
+
+    fn read_bus(&self, addr: u16) -> u8 {
         let addr = addr as usize;
 
+        // Boot ROM
+        if self.boot_rom_mapped && addr < self.boot_rom.len() {
+            let boot_rom_area = addr <= BOOT_ROM_LOW_END
+                || (BOOT_ROM_HIGH_START..=BOOT_ROM_HIGH_END).contains(&addr);
+
+            if boot_rom_area {
+                return self.boot_rom[addr];
+            }
+        }
+
+        // Cartridge ROM, the bus reads open with no cartridge inserted
+        if (CARTRIDGE_START..=CARTRIDGE_END).contains(&addr) {
+            return self.cartridge.as_ref().map_or(0xFF, |cartridge| cartridge.read_rom(addr));
+        }
+
+        // Cartridge RAM
+        if (EXT_WRAM_START..=EXT_WRAM_END).contains(&addr) {
+            return self.cartridge.as_ref().map_or(0xFF, |cartridge| cartridge.read_ram(addr));
+        }
+
         // VRAM
-        if addr >= VRAM_START && addr <= VRAM_END {
+        if (VRAM_START..=VRAM_END).contains(&addr) {
             return self.vram_banks[self.active_vram_bank][addr - VRAM_START];
         }
 
         // Switchable WRAM
-        if addr >= SW_WRAM_START && addr <= SW_WRAM_END {
+        if (SW_WRAM_START..=SW_WRAM_END).contains(&addr) {
             return self.sw_wram_banks[self.active_sw_wram_bank][addr - SW_WRAM_START];
         }
 
         // Echo RAM
-        if addr >= ECHO_WRAM_START && addr <= ECHO_WRAM_END {
-            return self.read((addr - (ECHO_WRAM_START - WRAM_START)) as u16);
+        if (ECHO_WRAM_START..=ECHO_WRAM_END).contains(&addr) {
+            return self.read_bus((addr - (ECHO_WRAM_START - WRAM_START)) as u16);
         }
 
         // Not usable
-        if addr >= NOT_USABLE_START && addr <= NOT_USABLE_END {
+        if (NOT_USABLE_START..=NOT_USABLE_END).contains(&addr) {
             let nibble = (addr & 0x00F0) as u8;
             return nibble | (nibble >> 4);
         }
 
         // Memory mapped registers
         if addr >= OTHER_START {
+            // Not present on monochrome models
+            if !self.model.is_cgb() && is_cgb_register(addr) {
+                return 0xFF;
+            }
+
+            // Joypad
+            if addr == P1_ADDR {
+                return self.joypad.read();
+            }
+
+            // Serial
+            if (SERIAL_START..=SERIAL_END).contains(&addr) {
+                return self.serial.read(addr);
+            }
+
+            // Timer
+            if (TIMER_START..=TIMER_END).contains(&addr) {
+                return self.timer.read(addr);
+            }
+
+            // Sound
+            if (APU_START..=APU_END).contains(&addr) {
+                return self.apu.read(addr);
+            }
+
+            // VRAM DMA
+            if (HDMA_START..=HDMA_END).contains(&addr) {
+                return self.hdma.read(addr);
+            }
+
+            // LCD status, bit 7 is unused
+            if addr == STAT_ADDR {
+                return 0x80 | self.fixed_memory[STAT_ADDR];
+            }
+
             // VRAM bank selection
             if addr == VBK_ADDR {
                 return 0xFE | (self.fixed_memory[VBK_ADDR] & 0x01);
             }
+
+            // Palette memories
+            match addr {
+                BCPS_ADDR => return self.bg_palettes.read_spec(),
+                BCPD_ADDR => return self.bg_palettes.read_data(self.is_drawing()),
+                OCPS_ADDR => return self.obj_palettes.read_spec(),
+                OCPD_ADDR => return self.obj_palettes.read_data(self.is_drawing()),
+                _ => {}
+            }
+
+            // Speed switch
+            if addr == KEY1_ADDR {
+                return 0x7E | (self.fixed_memory[KEY1_ADDR] & 0x81);
+            }
         }
 
         // Normal behavior
@@ -131,22 +309,110 @@ impl Memory {
     pub fn write(&mut self, addr: u16, value: u8) {
         let addr = addr as usize;
 
+        // During OAM DMA the CPU can only reach HRAM and the IO registers
+        if self.oam_dma.is_active() && addr < OTHER_START {
+            return;
+        }
+
+        // Cartridge ROM area, writes go to the memory bank controller
+        if (CARTRIDGE_START..=CARTRIDGE_END).contains(&addr) {
+            if let Some(cartridge) = self.cartridge.as_mut() {
+                cartridge.write_rom(addr, value);
+            }
+
+            return;
+        }
+
+        // Cartridge RAM
+        if (EXT_WRAM_START..=EXT_WRAM_END).contains(&addr) {
+            if let Some(cartridge) = self.cartridge.as_mut() {
+                cartridge.write_ram(addr, value);
+            }
+
+            return;
+        }
+
         // VRAM
-        if addr >= VRAM_START && addr <= VRAM_END {
+        if (VRAM_START..=VRAM_END).contains(&addr) {
             self.vram_banks[self.active_vram_bank][addr - VRAM_START] = value;
         }
 
         // Switchable WRAM
-        if addr >= SW_WRAM_START && addr <= SW_WRAM_END {
+        if (SW_WRAM_START..=SW_WRAM_END).contains(&addr) {
             self.sw_wram_banks[self.active_sw_wram_bank][addr - SW_WRAM_START] = value;
         }
 
         // Echo RAM
-        if addr >= ECHO_WRAM_START && addr <= ECHO_WRAM_END {
+        if (ECHO_WRAM_START..=ECHO_WRAM_END).contains(&addr) {
             return self.write((addr - (ECHO_WRAM_START - WRAM_START)) as u16, value);
         }
 
         if addr >= OTHER_START {
+            // Not present on monochrome models
+            if !self.model.is_cgb() && is_cgb_register(addr) {
+                return;
+            }
+
+            // Joypad, selecting a group with a pressed button also pulls a line low
+            if addr == P1_ADDR {
+                if self.joypad.write(value) {
+                    self.notify_interrupt(Interrupt::Joypad);
+                }
+
+                return;
+            }
+
+            // Serial
+            if (SERIAL_START..=SERIAL_END).contains(&addr) {
+                return self.serial.write(addr, value);
+            }
+
+            // Timer
+            if (TIMER_START..=TIMER_END).contains(&addr) {
+                return self.timer.write(addr, value);
+            }
+
+            // Sound
+            if (APU_START..=APU_END).contains(&addr) {
+                return self.apu.write(addr, value);
+            }
+
+            // VRAM DMA, general purpose transfers are done at once
+            if (HDMA_START..=HDMA_END).contains(&addr) {
+                self.hdma.write(addr, value);
+                self.run_hdma(false);
+
+                return;
+            }
+
+            // LCD status, mode and coincidence flag are read only
+            if addr == STAT_ADDR {
+                let read_only = self.fixed_memory[STAT_ADDR] & 0x07;
+                self.fixed_memory[STAT_ADDR] = (value & 0x78) | read_only;
+
+                return;
+            }
+
+            // LY is read only
+            if addr == LY_ADDR {
+                return;
+            }
+
+            // CPU mode selection, only the boot ROM can change it
+            if addr == KEY0_ADDR {
+                if self.boot_rom_mapped {
+                    self.dmg_compatibility = (value & 0x04) != 0;
+                    self.fixed_memory[KEY0_ADDR] = value;
+                }
+
+                return;
+            }
+
+            // Banking is not available in DMG compatibility mode
+            if self.dmg_compatibility && (addr == VBK_ADDR || addr == SVBK_ADDR) {
+                return;
+            }
+
             // VRAM bank selection
             if addr == VBK_ADDR {
                 self.active_vram_bank = (value & 0x01) as usize;
@@ -162,6 +428,33 @@ impl Memory {
                     0
                 };
             }
+
+            // Boot ROM can only be unmapped
+            if addr == BOOT_ADDR && (value & 0x01) != 0 {
+                self.boot_rom_mapped = false;
+            }
+
+            // OAM DMA
+            if addr == DMA_ADDR {
+                self.oam_dma.start(value);
+            }
+
+            // Palette memories
+            match addr {
+                BCPS_ADDR => return self.bg_palettes.write_spec(value),
+                BCPD_ADDR => return self.bg_palettes.write_data(value, self.is_drawing()),
+                OCPS_ADDR => return self.obj_palettes.write_spec(value),
+                OCPD_ADDR => return self.obj_palettes.write_data(value, self.is_drawing()),
+                _ => {}
+            }
+
+            // Speed switch, only the armed bit is writable
+            if addr == KEY1_ADDR {
+                let current_speed = self.fixed_memory[KEY1_ADDR] & 0x80;
+                self.fixed_memory[KEY1_ADDR] = current_speed | (value & 0x01);
+
+                return;
+            }
         }
 
         self.fixed_memory[addr] = value;
@@ -200,22 +493,233 @@ impl Memory {
         None
     }
 
-    pub fn notify_interrupt(&mut self, interrupt: Interrupt) {
-        let bit: u8 = match interrupt {
-            Interrupt::VBlank => 0,
-            Interrupt::Lcd => 1,
-            Interrupt::Timer => 2,
-            Interrupt::Serial => 3,
-            Interrupt::Joypad => 4
+    pub fn load_cartridge(&mut self, cartridge: Cartridge) {
+        self.cartridge = Some(cartridge);
+    }
+
+    pub fn load_boot_rom(&mut self, boot_rom: Vec<u8>) {
+        self.boot_rom = boot_rom;
+        self.boot_rom_mapped = true;
+    }
+
+    pub fn has_boot_rom(&self) -> bool {
+        !self.boot_rom.is_empty()
+    }
+
+    // IO registers and memory as left by the boot ROM
+    pub fn init_post_boot(&mut self, dmg_compatibility: bool) {
+        for &(addr, value) in POST_BOOT_REGISTERS.iter() {
+            self.write(addr as u16, value);
+        }
+
+        // The boot ROM is done and the interrupts it used are left requested
+        self.fixed_memory[BOOT_ADDR] = 0xFF;
+        self.fixed_memory[IF_ADDR] = 0xE1;
+
+        if !self.model.is_cgb() {
+            return;
+        }
+
+        if dmg_compatibility {
+            self.init_dmg_compatibility();
+        } else {
+            self.bg_palettes.fill_white();
+        }
+    }
+
+    // The boot ROM locks DMG cartridges into compatibility mode and colorizes them
+    fn init_dmg_compatibility(&mut self) {
+        self.dmg_compatibility = true;
+        self.fixed_memory[KEY0_ADDR] = 0x04;
+
+        // Sprites are prioritized by their X coordinate
+        self.fixed_memory[OPRI_ADDR] = 0x01;
+
+        let palettes = match self.cartridge.as_ref() {
+            Some(cartridge) => palette::select_compatibility_palettes(|addr| cartridge.read_rom(addr)),
+            None => palette::select_compatibility_palettes(|_| 0xFF)
         };
 
-        self.fixed_memory[IF_ADDR] |= 0x01 << bit;
+        self.bg_palettes.load_rgb555_palette(0, &palettes.bg);
+        self.obj_palettes.load_rgb555_palette(0, &palettes.obj0);
+        self.obj_palettes.load_rgb555_palette(1, &palettes.obj1);
+    }
+
+    // Monochrome model or CGB running a DMG cartridge
+    pub fn is_dmg_mode(&self) -> bool {
+        !self.model.is_cgb() || self.dmg_compatibility
+    }
+
+    pub fn cartridge(&self) -> Option<&Cartridge> {
+        self.cartridge.as_ref()
+    }
+
+    pub fn cartridge_mut(&mut self) -> Option<&mut Cartridge> {
+        self.cartridge.as_mut()
+    }
+
+    pub fn read_vram(&self, bank: usize, addr: usize) -> u8 {
+        self.vram_banks[bank][addr - VRAM_START]
+    }
+
+    pub fn bg_palette_color(&self, palette: u8, color: u8) -> u16 {
+        self.bg_palettes.color(palette, color)
+    }
+
+    pub fn obj_palette_color(&self, palette: u8, color: u8) -> u16 {
+        self.obj_palettes.color(palette, color)
+    }
+
+    pub fn read_oam(&self, offset: usize) -> u8 {
+        self.fixed_memory[OAM_START + offset]
+    }
+
+    pub fn notify_interrupt(&mut self, interrupt: Interrupt) {
+        self.fixed_memory[IF_ADDR] |= interrupt_mask(interrupt);
+    }
+
+    // The request is acknowledged when the CPU jumps to its vector
+    pub fn clear_interrupt(&mut self, interrupt: Interrupt) {
+        self.fixed_memory[IF_ADDR] &= !interrupt_mask(interrupt);
+    }
+
+    pub fn is_interrupt_requested(&self, interrupt: Interrupt) -> bool {
+        (self.fixed_memory[IF_ADDR] & interrupt_mask(interrupt)) != 0
+    }
+
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        if self.joypad.set_button(button, pressed) {
+            self.notify_interrupt(Interrupt::Joypad);
+        }
+    }
+
+    pub fn update_timer(&mut self, cycles: u8) {
+        if self.timer.update(cycles) {
+            self.notify_interrupt(Interrupt::Timer);
+        }
+    }
+
+    pub fn set_serial_link(&mut self, link: Box<dyn SerialLink>) {
+        self.serial.set_link(link);
+    }
+
+    pub fn update_serial(&mut self, cycles: u8) {
+        if self.serial.update(cycles) {
+            self.notify_interrupt(Interrupt::Serial);
+        }
+    }
+
+    pub fn update_apu(&mut self, cycles: u8) {
+        // The APU keeps its rate when the CPU runs in double speed
+        let t_cycles = if self.is_double_speed() { cycles as u16 * 2 } else { cycles as u16 * 4 };
+
+        let frame_sequencer_steps = self.timer.take_frame_sequencer_steps();
+        self.apu.update(t_cycles, frame_sequencer_steps);
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.apu.set_sample_rate(sample_rate);
+    }
+
+    pub fn take_audio_samples(&mut self) -> Vec<f32> {
+        self.apu.take_samples()
+    }
+
+    pub fn update_dma(&mut self, cycles: u8) {
+        for _ in 0..cycles {
+            if let Some((source, offset)) = self.oam_dma.step() {
+                self.fixed_memory[OAM_START + offset] = self.read_bus(source);
+            }
+        }
+    }
+
+    pub fn notify_hblank(&mut self) {
+        self.run_hdma(true);
+    }
+
+    // Returns true if the CPU has to be stalled for one more M-cycle
+    pub fn consume_dma_stall(&mut self) -> bool {
+        if self.dma_stall_cycles == 0 {
+            return false;
+        }
+
+        self.dma_stall_cycles -= 1;
+
+        true
+    }
+
+    fn run_hdma(&mut self, hblank: bool) {
+        while let Some((source, destination)) = self.hdma.next_block(hblank) {
+            for i in 0..HDMA_BLOCK_SIZE {
+                let value = self.read_bus(source.wrapping_add(i));
+                let offset = (destination + i) as usize & (VRAM_SIZE - 1);
+
+                self.vram_banks[self.active_vram_bank][offset] = value;
+            }
+
+            // Each block takes the same time regardless of the CPU speed
+            self.dma_stall_cycles += if self.is_double_speed() { 16 } else { 8 };
+
+            // A single block is copied on each HBlank
+            if hblank {
+                break;
+            }
+        }
+    }
+
+    pub fn reset_divider(&mut self) {
+        self.timer.reset_divider();
+    }
+
+    fn is_drawing(&self) -> bool {
+        let lcd_enabled = (self.fixed_memory[LCDC_ADDR] >> 7) != 0;
+        let mode = self.fixed_memory[STAT_ADDR] & 0x03;
+
+        lcd_enabled && mode == 0x03
+    }
+
+    pub fn is_speed_switch_armed(&self) -> bool {
+        (self.fixed_memory[KEY1_ADDR] & 0x01) != 0
+    }
+
+    pub fn set_double_speed(&mut self, double_speed: bool) {
+        // Switching speed also disarms the switch
+        self.fixed_memory[KEY1_ADDR] = if double_speed { 0x80 } else { 0x00 };
+        self.timer.set_double_speed(double_speed);
+    }
+
+    fn is_double_speed(&self) -> bool {
+        (self.fixed_memory[KEY1_ADDR] & 0x80) != 0
     }
 
     pub fn get_lcdc(&self) -> u8 {
         self.read(LCDC_ADDR as u16)
     }
 
+    pub fn get_stat(&self) -> u8 {
+        self.read(STAT_ADDR as u16)
+    }
+
+    pub fn set_stat_mode(&mut self, mode: u8) {
+        self.fixed_memory[STAT_ADDR] = (self.fixed_memory[STAT_ADDR] & 0xFC) | (mode & 0x03);
+    }
+
+    pub fn set_lyc_coincidence(&mut self, coincidence: bool) {
+        if coincidence {
+            self.fixed_memory[STAT_ADDR] |= 0x04;
+        } else {
+            self.fixed_memory[STAT_ADDR] &= !0x04;
+        }
+    }
+
+    pub fn set_ly(&mut self, ly: u8) {
+        self.fixed_memory[LY_ADDR] = ly;
+    }
+
+    pub fn get_lyc(&self) -> u8 {
+        self.read(LYC_ADDR as u16)
+    }
+
     pub fn get_scx(&self) -> u8 {
         self.read(SCX_ADDR as u16)
     }
@@ -224,5 +728,82 @@ impl Memory {
         self.read(SCY_ADDR as u16)
     }
 
+    pub fn get_wy(&self) -> u8 {
+        self.read(WY_ADDR as u16)
+    }
+
+    pub fn get_wx(&self) -> u8 {
+        self.read(WX_ADDR as u16)
+    }
+
+    pub fn get_bgp(&self) -> u8 {
+        self.read(BGP_ADDR as u16)
+    }
+
+    pub fn get_obp0(&self) -> u8 {
+        self.read(OBP0_ADDR as u16)
+    }
+
+    pub fn get_obp1(&self) -> u8 {
+        self.read(OBP1_ADDR as u16)
+    }
+
+    pub fn get_opri(&self) -> u8 {
+        self.read(OPRI_ADDR as u16)
+    }
+
+}
+
+// Registers with a defined value after boot, side effect free writes only.
+// The APU is powered first and channels aren't triggered so the boot sound doesn't replay
+const POST_BOOT_REGISTERS: [(usize, u8); 31] = [
+    (0xFF00, 0xCF), // P1
+    (0xFF02, 0x7F), // SC
+    (0xFF05, 0x00), // TIMA
+    (0xFF06, 0x00), // TMA
+    (0xFF07, 0xF8), // TAC
+    (0xFF26, 0xF1), // NR52
+    (0xFF10, 0x80), // NR10
+    (0xFF11, 0xBF), // NR11
+    (0xFF12, 0xF3), // NR12
+    (0xFF13, 0xFF), // NR13
+    (0xFF14, 0x3F), // NR14
+    (0xFF16, 0x3F), // NR21
+    (0xFF17, 0x00), // NR22
+    (0xFF18, 0xFF), // NR23
+    (0xFF19, 0x3F), // NR24
+    (0xFF1A, 0x7F), // NR30
+    (0xFF1B, 0xFF), // NR31
+    (0xFF1C, 0x9F), // NR32
+    (0xFF1D, 0xFF), // NR33
+    (0xFF1E, 0x3F), // NR34
+    (0xFF20, 0xFF), // NR41
+    (0xFF21, 0x00), // NR42
+    (0xFF22, 0x00), // NR43
+    (0xFF23, 0x3F), // NR44
+    (0xFF24, 0x77), // NR50
+    (0xFF25, 0xF3), // NR51
+    (0xFF40, 0x91), // LCDC
+    (0xFF42, 0x00), // SCY
+    (0xFF43, 0x00), // SCX
+    (0xFF47, 0xFC), // BGP
+    (0xFFFF, 0x00)  // IE
+];
+
+fn is_cgb_register(addr: usize) -> bool {
+    matches!(addr, KEY0_ADDR | KEY1_ADDR | VBK_ADDR | SVBK_ADDR)
+        || (HDMA_START..=HDMA_END).contains(&addr)
+        || (BCPS_ADDR..=OPRI_ADDR).contains(&addr)
 }
 
+fn interrupt_mask(interrupt: Interrupt) -> u8 {
+    let bit: u8 = match interrupt {
+        Interrupt::VBlank => 0,
+        Interrupt::Lcd => 1,
+        Interrupt::Timer => 2,
+        Interrupt::Serial => 3,
+        Interrupt::Joypad => 4
+    };
+
+    0x01 << bit
+}
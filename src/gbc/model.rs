@@ -0,0 +1,64 @@
+use std::fmt;
+use std::error::Error;
+
+// Boot ROM sizes, the CGB one also covers 0x0200-0x08FF
+const DMG_BOOT_ROM_SIZE: usize = 0x0100;
+const CGB_BOOT_ROM_SIZE: usize = 0x0900;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Model {
+    Dmg,
+    Mgb,
+    Cgb,
+    Agb
+}
+
+impl Model {
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dmg" => Some(Model::Dmg),
+            "mgb" => Some(Model::Mgb),
+            "cgb" => Some(Model::Cgb),
+            "agb" => Some(Model::Agb),
+            _ => None
+        }
+    }
+
+    // CGB features: VRAM/WRAM banking, color palettes, double speed and VRAM DMA
+    pub fn is_cgb(self) -> bool {
+        matches!(self, Model::Cgb | Model::Agb)
+    }
+
+    pub fn boot_rom_size(self) -> usize {
+        if self.is_cgb() {
+            CGB_BOOT_ROM_SIZE
+        } else {
+            DMG_BOOT_ROM_SIZE
+        }
+    }
+
+}
+
+#[derive(Debug)]
+pub enum BootRomError {
+    InvalidSize(Model, usize),
+
+    // The machine already started from the post boot state
+    CartridgeLoaded
+}
+
+impl fmt::Display for BootRomError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BootRomError::InvalidSize(model, size) => {
+                write!(f, "{:?} boot ROM must be {} bytes, got {}", model, model.boot_rom_size(), size)
+            },
+            BootRomError::CartridgeLoaded => write!(f, "boot ROM must be loaded before the cartridge")
+        }
+    }
+
+}
+
+impl Error for BootRomError {}
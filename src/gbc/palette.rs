@@ -0,0 +1,125 @@
+mod compatibility;
+
+pub use compatibility::select as select_compatibility_palettes;
+
+const PALETTE_RAM_SIZE: usize = 64;
+
+// Bytes per palette, 4 colors in RGB555 little endian
+const PALETTE_SIZE: usize = 8;
+
+const WHITE: u16 = 0x7FFF;
+
+// Monochrome models, RGB888 from lightest to darkest shade
+pub const DMG_SHADES: [u32; 4] = [0xE0F8D0, 0x88C070, 0x346856, 0x081820];
+pub const MGB_SHADES: [u32; 4] = [0xFFFFFF, 0xAAAAAA, 0x555555, 0x000000];
+
+pub struct PaletteMemory {
+    data: [u8; PALETTE_RAM_SIZE],
+
+    index: u8,
+    auto_increment: bool
+}
+
+impl PaletteMemory {
+
+    pub fn new() -> Self {
+        Self {
+            data: [0; PALETTE_RAM_SIZE],
+            index: 0,
+            auto_increment: false
+        }
+    }
+
+    pub fn fill_white(&mut self) {
+        for color in self.data.chunks_mut(2) {
+            color.copy_from_slice(&WHITE.to_le_bytes());
+        }
+    }
+
+    // Loads an RGB888 palette
+    pub fn load_palette(&mut self, palette: u8, colors: &[u32; 4]) {
+        let colors = colors.map(|rgb| {
+            let r = (rgb >> 19) & 0x1F;
+            let g = (rgb >> 11) & 0x1F;
+            let b = (rgb >> 3) & 0x1F;
+
+            ((b << 10) | (g << 5) | r) as u16
+        });
+
+        self.load_rgb555_palette(palette, &colors);
+    }
+
+    // Loads an RGB555 palette, as the boot ROM does for DMG cartridges
+    pub fn load_rgb555_palette(&mut self, palette: u8, colors: &[u16; 4]) {
+        for (i, color) in colors.iter().enumerate() {
+            let addr = palette as usize * PALETTE_SIZE + i * 2;
+
+            self.data[addr..addr + 2].copy_from_slice(&color.to_le_bytes());
+        }
+    }
+
+    // BCPS/OCPS
+    pub fn read_spec(&self) -> u8 {
+        let auto_increment = if self.auto_increment { 0x80 } else { 0x00 };
+
+        auto_increment | 0x40 | self.index
+    }
+
+    pub fn write_spec(&mut self, value: u8) {
+        self.index = value & 0x3F;
+        self.auto_increment = (value & 0x80) != 0;
+    }
+
+    // BCPD/OCPD, not accessible while the PPU is drawing
+    pub fn read_data(&self, blocked: bool) -> u8 {
+        if blocked {
+            0xFF
+        } else {
+            self.data[self.index as usize]
+        }
+    }
+
+    pub fn write_data(&mut self, value: u8, blocked: bool) {
+        if !blocked {
+            self.data[self.index as usize] = value;
+        }
+
+        // The index is incremented even if the write was ignored
+        if self.auto_increment {
+            self.index = (self.index + 1) & 0x3F;
+        }
+    }
+
+    // RGB555 color
+    pub fn color(&self, palette: u8, color: u8) -> u16 {
+        let addr = palette as usize * PALETTE_SIZE + color as usize * 2;
+
+        let lsb = self.data[addr] as u16;
+        let msb = self.data[addr + 1] as u16;
+
+        ((msb << 8) | lsb) & 0x7FFF
+    }
+
+}
+
+pub fn to_rgb888(color: u16, color_correction: bool) -> [u8; 3] {
+    let r = (color & 0x1F) as u32;
+    let g = ((color >> 5) & 0x1F) as u32;
+    let b = ((color >> 10) & 0x1F) as u32;
+
+    if color_correction {
+        // Mimic the washed out colors of the CGB LCD by mixing channels
+        let r_out = (r * 26 + g * 4 + b * 2).min(960) >> 2;
+        let g_out = (g * 24 + b * 8).min(960) >> 2;
+        let b_out = (r * 6 + g * 4 + b * 22).min(960) >> 2;
+
+        [r_out as u8, g_out as u8, b_out as u8]
+    } else {
+        // Scale the 5 bit channels to 8 bits
+        [
+            ((r << 3) | (r >> 2)) as u8,
+            ((g << 3) | (g >> 2)) as u8,
+            ((b << 3) | (b >> 2)) as u8
+        ]
+    }
+}
@@ -0,0 +1,259 @@
+// Header fields used by the boot ROM to pick a palette
+const TITLE_START: usize = 0x0134;
+const TITLE_END: usize = 0x0143;
+const NEW_LICENSEE_START: usize = 0x0144;
+const OLD_LICENSEE_ADDR: usize = 0x014B;
+
+// Only Nintendo titles get their own palette
+const NINTENDO_OLD_LICENSEE: u8 = 0x01;
+const USE_NEW_LICENSEE: u8 = 0x33;
+const NINTENDO_NEW_LICENSEE: [u8; 2] = *b"01";
+
+// BG, OBJ0 and OBJ1 palettes, RGB555
+pub struct CompatibilityPalettes {
+    pub bg: [u16; 4],
+    pub obj0: [u16; 4],
+    pub obj1: [u16; 4]
+}
+
+// Palettes stored in the boot ROM, RGB555 from lightest to darkest
+const PALETTE_COLORS: [u16; 120] = [
+    0x7FFF, 0x32BF, 0x00D0, 0x0000, // 0
+    0x639F, 0x4279, 0x15B0, 0x04CB, // 1
+    0x7FFF, 0x6E31, 0x454A, 0x0000, // 2
+    0x7FFF, 0x1BEF, 0x0200, 0x0000, // 3
+    0x7FFF, 0x421F, 0x1CF2, 0x0000, // 4
+    0x7FFF, 0x5294, 0x294A, 0x0000, // 5
+    0x7FFF, 0x03FF, 0x012F, 0x0000, // 6
+    0x7FFF, 0x03EF, 0x01D6, 0x0000, // 7
+    0x7FFF, 0x42B5, 0x3DC8, 0x0000, // 8
+    0x7E74, 0x03FF, 0x0180, 0x0000, // 9
+    0x67FF, 0x77AC, 0x1A13, 0x2D6B, // 10
+    0x7ED6, 0x4BFF, 0x2175, 0x0000, // 11
+    0x53FF, 0x4A5F, 0x7E52, 0x0000, // 12
+    0x4FFF, 0x7ED2, 0x3A4C, 0x1CE0, // 13
+    0x03ED, 0x7FFF, 0x255F, 0x0000, // 14
+    0x036A, 0x021F, 0x03FF, 0x7FFF, // 15
+    0x7FFF, 0x01DF, 0x0112, 0x0000, // 16
+    0x231F, 0x035F, 0x00F2, 0x0009, // 17
+    0x7FFF, 0x03EA, 0x011F, 0x0000, // 18
+    0x299F, 0x001A, 0x000C, 0x0000, // 19
+    0x7FFF, 0x027F, 0x001F, 0x0000, // 20
+    0x7FFF, 0x03E0, 0x0206, 0x0120, // 21
+    0x7FFF, 0x7EEB, 0x001F, 0x7C00, // 22
+    0x7FFF, 0x3FFF, 0x7E00, 0x001F, // 23
+    0x7FFF, 0x03FF, 0x001F, 0x0000, // 24
+    0x03FF, 0x001F, 0x000C, 0x0000, // 25
+    0x7FFF, 0x033F, 0x0193, 0x0000, // 26
+    0x0000, 0x4200, 0x037F, 0x7FFF, // 27
+    0x7FFF, 0x7E8C, 0x7C00, 0x0000, // 28
+    0x7FFF, 0x1BEF, 0x6180, 0x0000, // 29
+];
+
+// Offset of a palette in PALETTE_COLORS
+const fn start(palette: usize) -> usize {
+    palette * 4
+}
+
+// OBJ0, OBJ1 and BG palettes as offsets in PALETTE_COLORS. A few combinations don't start
+// at a palette boundary and mix the colors of two consecutive palettes, as the boot ROM does
+const PALETTE_COMBINATIONS: [(usize, usize, usize); 51] = [
+    (start(4), start(4), start(29)),        // 0
+    (start(18), start(18), start(18)),      // 1
+    (start(20), start(20), start(20)),      // 2
+    (start(24), start(24), start(24)),      // 3
+    (start(9), start(9), start(9)),         // 4
+    (start(0), start(0), start(0)),         // 5
+    (start(27), start(27), start(27)),      // 6
+    (start(5), start(5), start(5)),         // 7
+    (start(12), start(12), start(12)),      // 8
+    (start(26), start(26), start(26)),      // 9
+    (start(16), start(8), start(8)),        // 10
+    (start(4), start(28), start(28)),       // 11
+    (start(4), start(2), start(2)),         // 12
+    (start(3), start(4), start(4)),         // 13
+    (start(4), start(29), start(29)),       // 14
+    (start(28), start(4), start(28)),       // 15
+    (start(2), start(17), start(2)),        // 16
+    (start(16), start(16), start(8)),       // 17
+    (start(4), start(4), start(7)),         // 18
+    (start(4), start(4), start(18)),        // 19
+    (start(4), start(4), start(20)),        // 20
+    (start(19), start(19), start(9)),       // 21
+    (start(4) - 1, start(4) - 1, start(11)),// 22
+    (start(17), start(17), start(2)),       // 23
+    (start(4), start(4), start(2)),         // 24
+    (start(4), start(4), start(3)),         // 25
+    (start(28), start(28), start(0)),       // 26
+    (start(3), start(3), start(0)),         // 27
+    (start(0), start(0), start(1)),         // 28
+    (start(18), start(22), start(18)),      // 29
+    (start(20), start(22), start(20)),      // 30
+    (start(24), start(22), start(24)),      // 31
+    (start(16), start(22), start(8)),       // 32
+    (start(17), start(4), start(13)),       // 33
+    (start(28) - 1, start(0), start(14)),   // 34
+    (start(28) - 1, start(4), start(15)),   // 35
+    (start(19), start(22), start(9)),       // 36
+    (start(16), start(28), start(10)),      // 37
+    (start(4), start(23), start(28)),       // 38
+    (start(17), start(22), start(2)),       // 39
+    (start(4), start(0), start(2)),         // 40
+    (start(4), start(28), start(3)),        // 41
+    (start(28), start(3), start(0)),        // 42
+    (start(3), start(28), start(4)),        // 43
+    (start(21), start(28), start(4)),       // 44
+    (start(3), start(28), start(0)),        // 45
+    (start(25), start(3), start(28)),       // 46
+    (start(0), start(28), start(8)),        // 47
+    (start(4), start(3), start(28)),        // 48
+    (start(28), start(3), start(6)),        // 49
+    (start(4), start(28), start(29)),       // 50
+];
+
+// Title checksums and their palette combination, the titles are only for reference
+const TITLE_COMBINATIONS: [(u8, u8); 94] = [
+    (0x00, 0),   // Default
+    (0x88, 4),   // ALLEY WAY
+    (0x16, 5),   // YAKUMAN
+    (0x36, 35),  // BASEBALL, GAME&WATCH 2
+    (0xD1, 34),  // TENNIS
+    (0xDB, 3),   // TETRIS
+    (0xF2, 31),  // QIX
+    (0x3C, 15),  // DR.MARIO
+    (0x8C, 10),  // RADARMISSION
+    (0x92, 5),   // F1RACE
+    (0x3D, 19),  // YOSSY NO TAMAGO
+    (0x5C, 36),
+    (0x58, 7),   // X
+    (0xC9, 37),  // MARIOLAND2
+    (0x3E, 30),  // YOSSY NO COOKIE
+    (0x70, 44),  // ZELDA
+    (0x1D, 21),
+    (0x59, 32),
+    (0x69, 31),  // TETRIS FLASH
+    (0x19, 20),  // DONKEY KONG
+    (0x35, 5),   // MARIO'S PICROSS
+    (0xA8, 33),
+    (0x14, 13),  // POKEMON RED, GAMEBOYCAMERA G
+    (0xAA, 14),  // POKEMON GREEN
+    (0x75, 5),   // PICROSS 2
+    (0x95, 29),  // YOSSY NO PANEPON
+    (0x99, 5),   // KIRAKIRA KIDS
+    (0x34, 18),  // GAMEBOY GALLERY
+    (0x6F, 9),   // POCKETCAMERA
+    (0x15, 3),
+    (0xFF, 2),   // BALLOON KID
+    (0x97, 26),  // KINGOFTHEZOO
+    (0x4B, 25),  // DMG FOOTBALL
+    (0x90, 25),  // WORLD CUP
+    (0x17, 41),  // OTHELLO
+    (0x10, 42),  // SUPER RC PRO-AM
+    (0x39, 26),  // DYNABLASTER
+    (0xF7, 45),  // BOY AND HIS BLOB
+    (0xF6, 42),  // MEGAMAN
+    (0xA2, 45),  // STAR WARS-NOA
+    (0x49, 36),
+    (0x4E, 38),  // WAVERACE
+    (0x43, 26),
+    (0x68, 42),  // LOLO2
+    (0xE0, 30),  // YOSHI'S COOKIE
+    (0x8B, 41),  // MYSTIC QUEST
+    (0xF0, 34),
+    (0xCE, 34),  // TOPRANKINGTENNIS
+    (0x0C, 5),   // MANSELL
+    (0x29, 42),  // MEGAMAN3
+    (0xE8, 6),   // SPACE INVADERS
+    (0xB7, 5),   // GAME&WATCH
+    (0x86, 33),  // DONKEYKONGLAND95
+    (0x9A, 25),  // ASTEROIDS/MISCMD
+    (0x52, 42),  // STREET FIGHTER 2
+    (0x01, 42),  // DEFENDER/JOUST
+    (0x9D, 40),  // KILLERINSTINCT95
+    (0x71, 2),   // TETRIS BLAST
+    (0x9C, 16),  // PINOCCHIO
+    (0xBD, 25),
+    (0x5D, 42),  // BA.TOSHINDEN
+    (0x6D, 42),  // NETTOU KOF 95
+    (0x67, 5),
+    (0x3F, 0),   // TETRIS PLUS
+    (0x6B, 39),  // DONKEYKONGLAND 3
+
+    // Checksums shared by several titles, told apart by DUPLICATE_LETTERS
+    (0xB3, 36),
+    (0x46, 22),  // SUPER MARIOLAND
+    (0x28, 25),  // GOLF
+    (0xA5, 6),   // SOLARSTRIKER
+    (0xC6, 32),  // GBWARS
+    (0xD3, 12),  // KAERUNOTAMENI
+    (0x27, 36),
+    (0x61, 11),  // POKEMON BLUE
+    (0x18, 39),  // DONKEYKONGLAND
+    (0x66, 18),  // GAMEBOY GALLERY2
+    (0x6A, 39),  // DONKEYKONGLAND 2
+    (0xBF, 24),  // KID ICARUS
+    (0x0D, 31),  // TETRIS2
+    (0xF4, 50),
+    (0xB3, 17),  // MOGURANYA
+    (0x46, 46),
+    (0x28, 6),   // GALAGA&GALAXIAN
+    (0xA5, 27),  // BT2RAGNAROKWORLD
+    (0xC6, 0),   // KEN GRIFFEY JR
+    (0xD3, 47),
+    (0x27, 41),  // MAGNETIC SOCCER
+    (0x61, 41),  // VEGAS STAKES
+    (0x18, 0),
+    (0x66, 0),   // MILLI/CENTI/PEDE
+    (0x6A, 19),  // MARIO & YOSHI
+    (0xBF, 34),  // SOCCER
+    (0x0D, 23),  // POKEBOM
+    (0xF4, 18),  // G&W GALLERY
+    (0xB3, 29),  // TETRIS ATTACK
+];
+
+// Entries from here on only match if the 4th title letter matches too
+const FIRST_DUPLICATE: usize = 65;
+const DUPLICATE_LETTERS: &[u8; 29] = b"BEFAARBEKEK R-URAR INAILICE R";
+
+// Same lookup the CGB boot ROM does over the cartridge header
+pub fn select(rom: impl Fn(usize) -> u8) -> CompatibilityPalettes {
+    let nintendo = match rom(OLD_LICENSEE_ADDR) {
+        NINTENDO_OLD_LICENSEE => true,
+        USE_NEW_LICENSEE => {
+            [rom(NEW_LICENSEE_START), rom(NEW_LICENSEE_START + 1)] == NINTENDO_NEW_LICENSEE
+        },
+        _ => false
+    };
+
+    let combination = if nintendo {
+        let checksum = (TITLE_START..=TITLE_END)
+            .fold(0u8, |checksum, addr| checksum.wrapping_add(rom(addr)));
+
+        let fourth_letter = rom(TITLE_START + 3);
+
+        TITLE_COMBINATIONS.iter()
+            .enumerate()
+            .find(|&(index, &(title_checksum, _))| {
+                title_checksum == checksum
+                    && (index < FIRST_DUPLICATE || DUPLICATE_LETTERS[index - FIRST_DUPLICATE] == fourth_letter)
+            })
+            .map_or(0, |(_, &(_, combination))| combination)
+    } else {
+        0
+    };
+
+    let (obj0, obj1, bg) = PALETTE_COMBINATIONS[combination as usize];
+
+    CompatibilityPalettes {
+        bg: colors(bg),
+        obj0: colors(obj0),
+        obj1: colors(obj1)
+    }
+}
+
+fn colors(offset: usize) -> [u16; 4] {
+    let mut colors = [0; 4];
+    colors.copy_from_slice(&PALETTE_COLORS[offset..offset + 4]);
+
+    colors
+}
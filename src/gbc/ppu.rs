@@ -0,0 +1,291 @@
+mod background;
+mod sprites;
+
+use super::memory::Memory;
+use super::core::interrupt::Interrupt;
+use sprites::Sprite;
+use super::palette;
+
+pub const LCD_WIDTH: usize = 160;
+pub const LCD_HEIGHT: usize = 144;
+
+// RGB888 pixels, row by row
+pub type FrameBuffer = [u8; LCD_WIDTH * LCD_HEIGHT * 3];
+
+// Frame timing (dots)
+const DOTS_PER_LINE: u16 = 456;
+const LINES_PER_FRAME: u8 = 154;
+pub const DOTS_PER_FRAME: u32 = DOTS_PER_LINE as u32 * LINES_PER_FRAME as u32;
+const OAM_SCAN_DOTS: u16 = 80;
+const DRAWING_MIN_DOTS: u16 = 172;
+
+#[derive(Clone, Copy, PartialEq)]
+enum PpuMode {
+    HBlank,
+    VBlank,
+    OamScan,
+    Drawing
+}
+
+impl PpuMode {
+
+    fn stat_bits(self) -> u8 {
+        match self {
+            PpuMode::HBlank => 0x00,
+            PpuMode::VBlank => 0x01,
+            PpuMode::OamScan => 0x02,
+            PpuMode::Drawing => 0x03
+        }
+    }
+
+}
+
+// Background/window pixel before palette lookup
+#[derive(Clone, Copy)]
+struct BgPixel {
+    color: u8,
+    palette: u8,
+    priority: bool
+}
+
+impl BgPixel {
+
+    fn new() -> Self {
+        Self {
+            color: 0,
+            palette: 0,
+            priority: false
+        }
+    }
+
+}
+
+// Non transparent sprite pixel before palette lookup
+#[derive(Clone, Copy)]
+struct ObjPixel {
+    color: u8,
+    palette: u8,
+    dmg_palette: u8,
+    bg_priority: bool
+}
+
+pub struct Ppu {
+    framebuffer: FrameBuffer,
+    frame_ready: bool,
+
+    lcd_enabled: bool,
+    mode: PpuMode,
+
+    line: u8,
+    line_dot: u16,
+    drawing_dots: u16,
+
+    bg_line: [BgPixel; LCD_WIDTH],
+    obj_line: [Option<ObjPixel>; LCD_WIDTH],
+    line_sprites: Vec<Sprite>,
+
+    window_triggered: bool,
+    window_line: u8,
+
+    // STAT interrupt is requested on the rising edge of this signal
+    stat_signal: bool,
+
+    color_correction: bool
+}
+
+impl Ppu {
+
+    pub fn new() -> Self {
+        Self {
+            framebuffer: [0xFF; LCD_WIDTH * LCD_HEIGHT * 3],
+            frame_ready: false,
+            lcd_enabled: false,
+            mode: PpuMode::HBlank,
+            line: 0,
+            line_dot: 0,
+            drawing_dots: DRAWING_MIN_DOTS,
+            bg_line: [BgPixel::new(); LCD_WIDTH],
+            obj_line: [None; LCD_WIDTH],
+            line_sprites: Vec::new(),
+            window_triggered: false,
+            window_line: 0,
+            stat_signal: false,
+            color_correction: false
+        }
+    }
+
+    pub fn framebuffer(&self) -> &FrameBuffer {
+        &self.framebuffer
+    }
+
+    pub fn is_frame_ready(&self) -> bool {
+        self.frame_ready
+    }
+
+    pub fn set_color_correction(&mut self, enabled: bool) {
+        self.color_correction = enabled;
+    }
+
+    // Advances the PPU by a single dot
+    pub fn update(&mut self, memory: &mut Memory) {
+        self.frame_ready = false;
+
+        // Is screen enabled?
+        if (memory.get_lcdc() >> 7) == 0 {
+            if self.lcd_enabled {
+                self.disable_lcd(memory);
+            }
+
+            return;
+        }
+
+        if !self.lcd_enabled {
+            self.lcd_enabled = true;
+            self.line = 0;
+            self.line_dot = 0;
+        }
+
+        // Mode transitions within the current line
+        let next_mode = if self.line >= LCD_HEIGHT as u8 {
+            PpuMode::VBlank
+        } else if self.line_dot < OAM_SCAN_DOTS {
+            PpuMode::OamScan
+        } else if self.line_dot < OAM_SCAN_DOTS + self.drawing_dots {
+            PpuMode::Drawing
+        } else {
+            PpuMode::HBlank
+        };
+
+        if next_mode != self.mode {
+            self.enter_mode(next_mode, memory);
+        }
+
+        self.update_stat(memory);
+
+        // Advance to the next dot
+        self.line_dot += 1;
+
+        if self.line_dot == DOTS_PER_LINE {
+            self.line_dot = 0;
+            self.line = (self.line + 1) % LINES_PER_FRAME;
+
+            memory.set_ly(self.line);
+        }
+    }
+
+    fn enter_mode(&mut self, mode: PpuMode, memory: &mut Memory) {
+        match mode {
+            PpuMode::OamScan => {
+                self.scan_oam(memory);
+
+                // Fine scrolling discards pixels at the start of the line
+                self.drawing_dots = DRAWING_MIN_DOTS + (memory.get_scx() % 8) as u16;
+            },
+
+            PpuMode::Drawing => {},
+
+            PpuMode::HBlank => {
+                self.render_scanline(memory);
+                memory.notify_hblank();
+            },
+
+            PpuMode::VBlank => {
+                memory.notify_interrupt(Interrupt::VBlank);
+                self.frame_ready = true;
+
+                self.window_triggered = false;
+                self.window_line = 0;
+            }
+        }
+
+        self.mode = mode;
+        memory.set_stat_mode(mode.stat_bits());
+    }
+
+    fn update_stat(&mut self, memory: &mut Memory) {
+        let stat = memory.get_stat();
+
+        let coincidence = self.line == memory.get_lyc();
+        memory.set_lyc_coincidence(coincidence);
+
+        let signal = match self.mode {
+            PpuMode::HBlank => (stat & 0x08) != 0,
+            PpuMode::VBlank => (stat & 0x10) != 0,
+            PpuMode::OamScan => (stat & 0x20) != 0,
+            PpuMode::Drawing => false
+        } || (coincidence && (stat & 0x40) != 0);
+
+        if signal && !self.stat_signal {
+            memory.notify_interrupt(Interrupt::Lcd);
+        }
+
+        self.stat_signal = signal;
+    }
+
+    fn disable_lcd(&mut self, memory: &mut Memory) {
+        self.lcd_enabled = false;
+        self.mode = PpuMode::HBlank;
+        self.line = 0;
+        self.line_dot = 0;
+        self.stat_signal = false;
+        self.window_triggered = false;
+        self.window_line = 0;
+
+        memory.set_ly(0);
+        memory.set_stat_mode(self.mode.stat_bits());
+
+        // A disabled LCD shows a blank screen
+        self.framebuffer = [0xFF; LCD_WIDTH * LCD_HEIGHT * 3];
+        self.frame_ready = true;
+    }
+
+    fn render_scanline(&mut self, memory: &Memory) {
+        self.render_background(memory);
+        self.render_sprites(memory);
+
+        let dmg_mode = memory.is_dmg_mode();
+
+        // LCDC bit 0 is the master priority on CGB, if cleared sprites are always on top.
+        // In DMG mode it disables the background and window instead
+        let master_priority = memory.get_lcdc() & 0x01 != 0x00;
+
+        if dmg_mode && !master_priority {
+            self.bg_line = [BgPixel::new(); LCD_WIDTH];
+        }
+
+        let line_start = self.line as usize * LCD_WIDTH * 3;
+
+        for x in 0..LCD_WIDTH {
+            let bg_pixel = self.bg_line[x];
+
+            let obj_pixel = match self.obj_line[x] {
+                Some(obj_pixel) if !master_priority || bg_pixel.color == 0 => Some(obj_pixel),
+                Some(obj_pixel) if !bg_pixel.priority && !obj_pixel.bg_priority => Some(obj_pixel),
+                _ => None
+            };
+
+            // DMG colors go through BGP/OBP0/OBP1 before the compatibility palettes
+            let color = match obj_pixel {
+                Some(obj_pixel) if dmg_mode => {
+                    let obp = if obj_pixel.dmg_palette == 0 { memory.get_obp0() } else { memory.get_obp1() };
+                    memory.obj_palette_color(obj_pixel.palette, dmg_shade(obp, obj_pixel.color))
+                },
+                Some(obj_pixel) => memory.obj_palette_color(obj_pixel.palette, obj_pixel.color),
+                None if dmg_mode => {
+                    memory.bg_palette_color(bg_pixel.palette, dmg_shade(memory.get_bgp(), bg_pixel.color))
+                },
+                None => memory.bg_palette_color(bg_pixel.palette, bg_pixel.color)
+            };
+
+            let pixel = line_start + x * 3;
+
+            self.framebuffer[pixel..pixel + 3].copy_from_slice(&palette::to_rgb888(color, self.color_correction));
+        }
+    }
+
+}
+
+// Each color index is mapped to one of the four shades of a DMG palette register
+fn dmg_shade(palette: u8, color: u8) -> u8 {
+    (palette >> (color * 2)) & 0x03
+}
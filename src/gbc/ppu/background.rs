@@ -0,0 +1,95 @@
+use super::{Ppu, BgPixel, LCD_WIDTH};
+use super::Memory;
+use super::super::memory::{VRAM_CHAR_DATA_START, VRAM_BG_DATA_1_START, VRAM_BG_DATA_2_START};
+
+const TILE_MAP_WIDTH: usize = 32;
+const TILE_SIZE: usize = 16;
+
+impl Ppu {
+
+    pub fn render_background(&mut self, memory: &Memory) {
+        let lcdc = memory.get_lcdc();
+
+        let bg_map = if (lcdc >> 3) & 0x01 == 0x00 {
+            VRAM_BG_DATA_1_START
+        } else {
+            VRAM_BG_DATA_2_START
+        };
+
+        let window_map = if (lcdc >> 6) & 0x01 == 0x00 {
+            VRAM_BG_DATA_1_START
+        } else {
+            VRAM_BG_DATA_2_START
+        };
+
+        // The window is triggered once LY matches WY during the frame
+        if self.line == memory.get_wy() {
+            self.window_triggered = true;
+        }
+
+        let wx = memory.get_wx() as usize;
+        let window_visible = (lcdc >> 5) & 0x01 != 0x00 && self.window_triggered && wx < LCD_WIDTH + 7;
+
+        let scx = memory.get_scx();
+        let scy = memory.get_scy();
+
+        for x in 0..LCD_WIDTH {
+            self.bg_line[x] = if window_visible && x + 7 >= wx {
+                let window_x = (x + 7 - wx) as u8;
+                self.fetch_bg_pixel(memory, window_map, window_x, self.window_line)
+            } else {
+                let bg_x = scx.wrapping_add(x as u8);
+                let bg_y = scy.wrapping_add(self.line);
+                self.fetch_bg_pixel(memory, bg_map, bg_x, bg_y)
+            };
+        }
+
+        // The window keeps its own line counter, only advanced when it's drawn
+        if window_visible {
+            self.window_line += 1;
+        }
+    }
+
+    fn fetch_bg_pixel(&self, memory: &Memory, tile_map: usize, x: u8, y: u8) -> BgPixel {
+        let map_offset = (y as usize / 8) * TILE_MAP_WIDTH + (x as usize / 8);
+
+        let tile_index = memory.read_vram(0, tile_map + map_offset);
+
+        // CGB attributes live in bank 1, DMG cartridges don't know about them
+        let attributes = if memory.is_dmg_mode() {
+            0x00
+        } else {
+            memory.read_vram(1, tile_map + map_offset)
+        };
+        let palette = attributes & 0x07;
+        let bank = ((attributes >> 3) & 0x01) as usize;
+        let x_flip = (attributes >> 5) & 0x01 != 0x00;
+        let y_flip = (attributes >> 6) & 0x01 != 0x00;
+        let priority = (attributes >> 7) & 0x01 != 0x00;
+
+        let tile_addr = if (memory.get_lcdc() >> 4) & 0x01 != 0x00 {
+            VRAM_CHAR_DATA_START + tile_index as usize * TILE_SIZE
+        } else {
+            // Signed addressing from 0x9000
+            let signed_offset = (tile_index as i8) as isize * TILE_SIZE as isize;
+            (VRAM_CHAR_DATA_START as isize + 0x1000 + signed_offset) as usize
+        };
+
+        let tile_x = if x_flip { 7 - (x % 8) } else { x % 8 };
+        let tile_y = if y_flip { 7 - (y % 8) } else { y % 8 };
+
+        let line_addr = tile_addr + tile_y as usize * 2;
+        let lsb = memory.read_vram(bank, line_addr);
+        let msb = memory.read_vram(bank, line_addr + 1);
+
+        let bit = 7 - tile_x;
+        let color = (((msb >> bit) & 0x01) << 1) | ((lsb >> bit) & 0x01);
+
+        BgPixel {
+            color,
+            palette,
+            priority
+        }
+    }
+
+}
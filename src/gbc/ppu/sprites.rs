@@ -0,0 +1,144 @@
+use super::{Ppu, ObjPixel, LCD_WIDTH};
+use super::Memory;
+use super::super::memory::VRAM_CHAR_DATA_START;
+
+const OAM_ENTRIES: usize = 40;
+const OAM_ENTRY_SIZE: usize = 4;
+const MAX_SPRITES_PER_LINE: usize = 10;
+const TILE_SIZE: usize = 16;
+
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    oam_index: usize,
+    y: u8,
+    x: u8,
+    tile: u8,
+    attributes: u8
+}
+
+impl Ppu {
+
+    pub fn scan_oam(&mut self, memory: &Memory) {
+        let height = sprite_height(memory.get_lcdc());
+
+        self.line_sprites.clear();
+
+        for oam_index in 0..OAM_ENTRIES {
+            let entry = oam_index * OAM_ENTRY_SIZE;
+            let y = memory.read_oam(entry);
+
+            // Y is stored with an offset of 16 lines
+            let line = self.line as u16 + 16;
+            if line < y as u16 || line >= y as u16 + height as u16 {
+                continue;
+            }
+
+            self.line_sprites.push(Sprite {
+                oam_index,
+                y,
+                x: memory.read_oam(entry + 1),
+                tile: memory.read_oam(entry + 2),
+                attributes: memory.read_oam(entry + 3)
+            });
+
+            // Only the first sprites found in OAM are drawn
+            if self.line_sprites.len() == MAX_SPRITES_PER_LINE {
+                break;
+            }
+        }
+
+        // Drawing priority, first sprites win
+        if memory.get_opri() & 0x01 != 0x00 {
+            // DMG style, lower X wins, ties resolved by OAM index
+            self.line_sprites.sort_by_key(|sprite| (sprite.x, sprite.oam_index));
+        }
+    }
+
+    pub fn render_sprites(&mut self, memory: &Memory) {
+        self.obj_line = [None; LCD_WIDTH];
+
+        let lcdc = memory.get_lcdc();
+
+        // Are sprites enabled?
+        if (lcdc >> 1) & 0x01 == 0x00 {
+            return;
+        }
+
+        let height = sprite_height(lcdc);
+        let obj_line = &mut self.obj_line;
+
+        for sprite in self.line_sprites.iter() {
+            let dmg_palette = (sprite.attributes >> 4) & 0x01;
+
+            // DMG cartridges only have the first bank and OBP0/OBP1
+            let (palette, bank) = if memory.is_dmg_mode() {
+                (dmg_palette, 0)
+            } else {
+                (sprite.attributes & 0x07, ((sprite.attributes >> 3) & 0x01) as usize)
+            };
+            let x_flip = (sprite.attributes >> 5) & 0x01 != 0x00;
+            let y_flip = (sprite.attributes >> 6) & 0x01 != 0x00;
+            let bg_priority = (sprite.attributes >> 7) & 0x01 != 0x00;
+
+            // The sprite size may have changed since the OAM scan
+            let mut sprite_y = (self.line + 16 - sprite.y) as usize;
+            if sprite_y >= height as usize {
+                continue;
+            }
+
+            if y_flip {
+                sprite_y = height as usize - 1 - sprite_y;
+            }
+
+            // 8x16 sprites ignore the lowest bit of the tile index
+            let tile = if height == 16 {
+                (sprite.tile & 0xFE) as usize + sprite_y / 8
+            } else {
+                sprite.tile as usize
+            };
+
+            let line_addr = VRAM_CHAR_DATA_START + tile * TILE_SIZE + (sprite_y % 8) * 2;
+            let lsb = memory.read_vram(bank, line_addr);
+            let msb = memory.read_vram(bank, line_addr + 1);
+
+            for tile_x in 0..8 {
+                // X is stored with an offset of 8 pixels
+                let x = sprite.x as usize + tile_x;
+                if !(8..LCD_WIDTH + 8).contains(&x) {
+                    continue;
+                }
+
+                let x = x - 8;
+
+                // A higher priority sprite already owns this pixel
+                if obj_line[x].is_some() {
+                    continue;
+                }
+
+                let bit = if x_flip { tile_x } else { 7 - tile_x };
+                let color = (((msb >> bit) & 0x01) << 1) | ((lsb >> bit) & 0x01);
+
+                // Color 0 is transparent
+                if color == 0 {
+                    continue;
+                }
+
+                obj_line[x] = Some(ObjPixel {
+                    color,
+                    palette,
+                    dmg_palette,
+                    bg_priority
+                });
+            }
+        }
+    }
+
+}
+
+fn sprite_height(lcdc: u8) -> u8 {
+    if (lcdc >> 2) & 0x01 == 0x00 {
+        8
+    } else {
+        16
+    }
+}
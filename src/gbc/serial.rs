@@ -0,0 +1,221 @@
+mod tcp;
+mod capture;
+
+pub use tcp::TcpLink;
+pub use capture::{SerialCapture, CaptureTarget};
+
+// Serial registers
+const SB_ADDR: usize = 0xFF01;
+const SC_ADDR: usize = 0xFF02;
+
+// SC bits
+const TRANSFER_ENABLE: u8 = 0x80;
+const FAST_CLOCK: u8 = 0x02;
+const INTERNAL_CLOCK: u8 = 0x01;
+
+// M-cycles per bit with the internal clock, 8192 Hz or 262144 Hz on CGB
+const NORMAL_BIT_CYCLES: u16 = 128;
+const FAST_BIT_CYCLES: u16 = 4;
+
+// M-cycles between link polls while waiting for the peer
+const LINK_POLL_CYCLES: u16 = 256;
+
+// The other end of the link cable, it moves along with the machine across threads
+pub trait SerialLink: Send {
+    // Internal clock, we drive a transfer of our byte
+    fn start_transfer(&mut self, value: u8);
+
+    // External clock, our byte is ready for when the peer drives a transfer
+    fn arm(&mut self, _value: u8) {}
+
+    // The transfer was disabled before completing
+    fn cancel(&mut self) {}
+
+    // Peer's byte once the started or armed transfer is complete
+    fn poll(&mut self) -> Option<u8>;
+}
+
+// Nothing plugged, the input line is pulled high and nobody drives our clock
+#[derive(Default)]
+pub struct Disconnected {
+    transferring: bool
+}
+
+impl SerialLink for Disconnected {
+
+    fn start_transfer(&mut self, _value: u8) {
+        self.transferring = true;
+    }
+
+    fn cancel(&mut self) {
+        self.transferring = false;
+    }
+
+    fn poll(&mut self) -> Option<u8> {
+        if !self.transferring {
+            return None;
+        }
+
+        self.transferring = false;
+
+        Some(0xFF)
+    }
+
+}
+
+pub struct Serial {
+    link: Box<dyn SerialLink>,
+    cgb: bool,
+
+    sb: u8,
+    sc: u8,
+
+    // Byte being shifted in during an internally clocked transfer,
+    // the clock is held until the peer's byte is known
+    incoming: Option<u8>,
+    bits_left: u8,
+    bit_cycles: u16,
+
+    poll_cycles: u16
+}
+
+impl Serial {
+
+    pub fn new(cgb: bool) -> Self {
+        Self {
+            link: Box::new(Disconnected::default()),
+            cgb,
+            sb: 0,
+            sc: 0,
+            incoming: None,
+            bits_left: 0,
+            bit_cycles: 0,
+            poll_cycles: 0
+        }
+    }
+
+    pub fn set_link(&mut self, link: Box<dyn SerialLink>) {
+        self.link = link;
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            SB_ADDR => self.sb,
+            SC_ADDR => {
+                let unused = if self.cgb { 0x7C } else { 0x7E };
+                unused | self.sc
+            },
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        match addr {
+            SB_ADDR => self.sb = value,
+
+            SC_ADDR => {
+                let mask = if self.cgb {
+                    TRANSFER_ENABLE | FAST_CLOCK | INTERNAL_CLOCK
+                } else {
+                    TRANSFER_ENABLE | INTERNAL_CLOCK
+                };
+
+                if self.is_transferring() {
+                    self.link.cancel();
+                }
+
+                self.sc = value & mask;
+
+                if self.is_transferring() {
+                    // The whole byte is exchanged up front and shifted in bit by bit
+                    if self.is_internal_clock() {
+                        self.link.start_transfer(self.sb);
+                    } else {
+                        self.link.arm(self.sb);
+                    }
+
+                    self.incoming = None;
+                    self.bits_left = 8;
+                    self.bit_cycles = 0;
+
+                    // The first poll happens right away
+                    self.poll_cycles = LINK_POLL_CYCLES;
+                }
+            },
+
+            _ => {}
+        }
+    }
+
+    // Returns true if the serial interrupt has to be requested
+    pub fn update(&mut self, cycles: u8) -> bool {
+        if !self.is_transferring() {
+            return false;
+        }
+
+        let incoming = match self.incoming {
+            Some(incoming) => incoming,
+
+            // Waiting for the peer, it is only polled every few cycles
+            None => {
+                self.poll_cycles = self.poll_cycles.saturating_add(cycles as u16);
+
+                if self.poll_cycles < LINK_POLL_CYCLES {
+                    return false;
+                }
+
+                self.poll_cycles = 0;
+
+                let Some(incoming) = self.link.poll() else {
+                    return false;
+                };
+
+                // With an external clock the peer already shifted the whole byte
+                if !self.is_internal_clock() {
+                    self.sb = incoming;
+                    self.complete_transfer();
+
+                    return true;
+                }
+
+                self.incoming = Some(incoming);
+
+                incoming
+            }
+        };
+
+        let period = if (self.sc & FAST_CLOCK) != 0 { FAST_BIT_CYCLES } else { NORMAL_BIT_CYCLES };
+
+        self.bit_cycles += cycles as u16;
+
+        while self.bit_cycles >= period && self.bits_left > 0 {
+            self.bit_cycles -= period;
+            self.bits_left -= 1;
+
+            // MSB first
+            let bit = (incoming >> self.bits_left) & 0x01;
+            self.sb = (self.sb << 1) | bit;
+        }
+
+        if self.bits_left == 0 {
+            self.complete_transfer();
+
+            return true;
+        }
+
+        false
+    }
+
+    fn complete_transfer(&mut self) {
+        self.sc &= !TRANSFER_ENABLE;
+    }
+
+    fn is_transferring(&self) -> bool {
+        (self.sc & TRANSFER_ENABLE) != 0
+    }
+
+    fn is_internal_clock(&self) -> bool {
+        (self.sc & INTERNAL_CLOCK) != 0
+    }
+
+}
@@ -0,0 +1,70 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use super::SerialLink;
+
+// Where the captured bytes go
+pub enum CaptureTarget {
+    Buffer,
+    Stdout
+}
+
+// No cable connected, but every byte sent is recorded. Test ROMs print their results this way
+pub struct SerialCapture {
+    target: CaptureTarget,
+    buffer: Arc<Mutex<Vec<u8>>>,
+
+    transferring: bool
+}
+
+impl SerialCapture {
+
+    pub fn new(target: CaptureTarget) -> Self {
+        Self {
+            target,
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            transferring: false
+        }
+    }
+
+    // Shared handle to the captured bytes, still readable once the link is plugged
+    pub fn buffer(&self) -> Arc<Mutex<Vec<u8>>> {
+        Arc::clone(&self.buffer)
+    }
+
+}
+
+impl SerialLink for SerialCapture {
+
+    fn start_transfer(&mut self, value: u8) {
+        self.transferring = true;
+
+        match self.target {
+            CaptureTarget::Buffer => self.buffer.lock().unwrap().push(value),
+            CaptureTarget::Stdout => {
+                let mut stdout = io::stdout();
+
+                // Flushed right away so the output isn't lost if the emulator is killed
+                if let Err(error) = stdout.write_all(&[value]).and_then(|_| stdout.flush()) {
+                    eprintln!("Error writing serial output: {}", error);
+                }
+            }
+        }
+    }
+
+    fn cancel(&mut self) {
+        self.transferring = false;
+    }
+
+    // Like a disconnected cable, the input line reads high
+    fn poll(&mut self) -> Option<u8> {
+        if !self.transferring {
+            return None;
+        }
+
+        self.transferring = false;
+
+        Some(0xFF)
+    }
+
+}
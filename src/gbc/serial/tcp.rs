@@ -0,0 +1,350 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write, ErrorKind};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+use super::SerialLink;
+
+// Messages are [kind, value]
+const MESSAGE_SIZE: usize = 2;
+
+// The clocking side sends its byte when it starts the transfer,
+// the other side sends its own when it arms the transfer with an external clock
+const MESSAGE_TRANSFER: u8 = 0x01;
+const MESSAGE_ARM: u8 = 0x02;
+const MESSAGE_CANCEL: u8 = 0x03;
+
+// Messages are tiny, a full socket buffer only lasts a moment
+const SEND_RETRY_INTERVAL: Duration = Duration::from_micros(100);
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Idle,
+    Transferring,
+    Armed
+}
+
+// Byte the peer offered for one of its transfers
+#[derive(Clone, Copy)]
+enum Offer {
+    Transfer(u8),
+    Arm(u8)
+}
+
+// Link cable to another emulator over TCP. Each transfer pairs the clocking side's TRANSFER
+// with the other side's ARM, so both always exchange the bytes they had when they started.
+// Nothing blocks: the clocking side's transfer is just held until the peer has armed
+pub struct TcpLink {
+    stream: Option<TcpStream>,
+    buffer: Vec<u8>,
+
+    state: State,
+
+    // Offers not paired yet, in the order the peer made them. Each transfer takes exactly
+    // one, so a peer running ahead can't replace the byte meant for the current transfer
+    peer_offers: VecDeque<Offer>
+}
+
+impl TcpLink {
+
+    // Waits for the other emulator to connect
+    pub fn listen(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let (stream, _) = listener.accept()?;
+
+        Self::new(stream)
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::new(TcpStream::connect(addr)?)
+    }
+
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+
+        Ok(Self {
+            stream: Some(stream),
+            buffer: Vec::new(),
+            state: State::Idle,
+            peer_offers: VecDeque::new()
+        })
+    }
+
+    fn send(&mut self, kind: u8, value: u8) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+
+        // Wait for room instead of splitting messages
+        let mut message = &[kind, value][..];
+
+        while !message.is_empty() {
+            match stream.write(message) {
+                Ok(written) => message = &message[written..],
+                Err(error) if error.kind() == ErrorKind::WouldBlock => thread::sleep(SEND_RETRY_INTERVAL),
+                Err(error) => return self.disconnect(error)
+            }
+        }
+    }
+
+    // Queues whatever arrived
+    fn receive(&mut self) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+
+        let mut data = [0u8; 64];
+
+        loop {
+            match stream.read(&mut data) {
+                Ok(0) => return self.disconnect(io::Error::from(ErrorKind::UnexpectedEof)),
+                Ok(read) => self.buffer.extend_from_slice(&data[..read]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => return self.disconnect(error)
+            }
+        }
+
+        let messages: Vec<u8> = self.buffer.drain(..self.buffer.len() / MESSAGE_SIZE * MESSAGE_SIZE).collect();
+
+        for message in messages.chunks(MESSAGE_SIZE) {
+            match message[0] {
+                MESSAGE_TRANSFER => self.peer_offers.push_back(Offer::Transfer(message[1])),
+                MESSAGE_ARM => self.peer_offers.push_back(Offer::Arm(message[1])),
+                // The peer only cancels its latest offer, which is gone already if it was paired
+                _ => {
+                    self.peer_offers.pop_back();
+                }
+            }
+        }
+    }
+
+    fn disconnect(&mut self, error: io::Error) {
+        eprintln!("Link cable disconnected: {}", error);
+
+        self.stream = None;
+        self.buffer.clear();
+    }
+
+}
+
+impl SerialLink for TcpLink {
+
+    fn start_transfer(&mut self, value: u8) {
+        self.state = State::Transferring;
+        self.send(MESSAGE_TRANSFER, value);
+    }
+
+    fn arm(&mut self, value: u8) {
+        self.state = State::Armed;
+        self.send(MESSAGE_ARM, value);
+    }
+
+    fn cancel(&mut self) {
+        if self.state != State::Idle {
+            self.state = State::Idle;
+            self.send(MESSAGE_CANCEL, 0);
+        }
+    }
+
+    fn poll(&mut self) -> Option<u8> {
+        self.receive();
+
+        let incoming = match self.state {
+            State::Idle => None,
+
+            // Both sides clocking at once just swap their bytes, the peer does the same
+            State::Transferring => match self.peer_offers.pop_front() {
+                Some(Offer::Transfer(value) | Offer::Arm(value)) => Some(value),
+
+                // Nobody answers a disconnected cable
+                None if self.stream.is_none() => Some(0xFF),
+                None => None
+            },
+
+            // An armed peer is waiting for a clock too, its offer is for our next transfer
+            State::Armed => match self.peer_offers.front() {
+                Some(&Offer::Transfer(value)) => {
+                    self.peer_offers.pop_front();
+                    Some(value)
+                },
+                _ => None
+            }
+        };
+
+        if incoming.is_some() {
+            self.state = State::Idle;
+        }
+
+        incoming
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn connected_pair() -> (TcpLink, TcpLink) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        (TcpLink::new(server).unwrap(), TcpLink::new(client).unwrap())
+    }
+
+    // Polls both ends like two running emulators until they have completed their transfers
+    fn exchange(first: &mut TcpLink, second: &mut TcpLink) -> (u8, u8) {
+        let timer = Instant::now();
+        let (mut first_byte, mut second_byte) = (None, None);
+
+        while first_byte.is_none() || second_byte.is_none() {
+            assert!(timer.elapsed() < Duration::from_secs(5), "transfer never completed");
+
+            first_byte = first_byte.or_else(|| first.poll());
+            second_byte = second_byte.or_else(|| second.poll());
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        (first_byte.unwrap(), second_byte.unwrap())
+    }
+
+    // Polls one end alone until its transfer completes
+    fn complete(link: &mut TcpLink) -> u8 {
+        let timer = Instant::now();
+
+        loop {
+            if let Some(incoming) = link.poll() {
+                return incoming;
+            }
+
+            assert!(timer.elapsed() < Duration::from_secs(5), "transfer never completed");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn master_waits_for_slave_to_arm() {
+        let (mut master, mut slave) = connected_pair();
+
+        master.start_transfer(0x12);
+
+        // The slave isn't listening yet, the master's transfer is held
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(master.poll(), None);
+
+        slave.arm(0x34);
+
+        assert_eq!(exchange(&mut master, &mut slave), (0x34, 0x12));
+    }
+
+    #[test]
+    fn slave_armed_before_master_clocks() {
+        let (mut master, mut slave) = connected_pair();
+
+        slave.arm(0x56);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(slave.poll(), None);
+
+        master.start_transfer(0x78);
+
+        assert_eq!(exchange(&mut master, &mut slave), (0x56, 0x78));
+    }
+
+    #[test]
+    fn both_sides_clocking_swap_bytes() {
+        let (mut first, mut second) = connected_pair();
+
+        first.start_transfer(0x9A);
+        second.start_transfer(0xBC);
+
+        assert_eq!(exchange(&mut first, &mut second), (0xBC, 0x9A));
+    }
+
+    #[test]
+    fn cancelled_arm_is_not_used() {
+        let (mut master, mut slave) = connected_pair();
+
+        slave.arm(0x11);
+        slave.cancel();
+        slave.arm(0x22);
+
+        master.start_transfer(0x33);
+
+        assert_eq!(exchange(&mut master, &mut slave), (0x22, 0x33));
+    }
+
+    #[test]
+    fn cancel_drops_only_latest_offer() {
+        let (mut master, mut slave) = connected_pair();
+
+        master.start_transfer(0x12);
+        slave.arm(0x34);
+        assert_eq!(complete(&mut slave), 0x12);
+
+        // The slave's next transfer is queued behind its first one, then cancelled
+        slave.start_transfer(0x56);
+        slave.cancel();
+        thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(complete(&mut master), 0x34);
+
+        master.arm(0x78);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(master.poll(), None);
+    }
+
+    #[test]
+    fn disconnected_peer_reads_high() {
+        let (mut master, slave) = connected_pair();
+        drop(slave);
+
+        master.start_transfer(0x44);
+
+        assert_eq!(complete(&mut master), 0xFF);
+    }
+
+    #[test]
+    fn slave_arming_again_keeps_pending_byte() {
+        let (mut master, mut slave) = connected_pair();
+
+        master.start_transfer(0x12);
+        slave.arm(0x34);
+        assert_eq!(complete(&mut slave), 0x12);
+
+        // The slave runs ahead and arms the next transfer before the master polls
+        slave.arm(0x56);
+        thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(complete(&mut master), 0x34);
+
+        master.start_transfer(0x78);
+
+        assert_eq!(exchange(&mut master, &mut slave), (0x56, 0x78));
+    }
+
+    #[test]
+    fn master_transferring_again_keeps_pending_byte() {
+        let (mut master, mut slave) = connected_pair();
+
+        slave.arm(0x12);
+        master.start_transfer(0x34);
+        assert_eq!(complete(&mut master), 0x12);
+
+        // The master starts the next transfer before the slave polls
+        master.start_transfer(0x56);
+        thread::sleep(Duration::from_millis(20));
+
+        assert_eq!(complete(&mut slave), 0x34);
+
+        slave.arm(0x78);
+
+        assert_eq!(exchange(&mut master, &mut slave), (0x78, 0x56));
+    }
+
+}
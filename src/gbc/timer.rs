@@ -0,0 +1,223 @@
+// Timer registers
+const DIV_ADDR: usize = 0xFF04;
+const TIMA_ADDR: usize = 0xFF05;
+const TMA_ADDR: usize = 0xFF06;
+const TAC_ADDR: usize = 0xFF07;
+
+pub struct Timer {
+    // DIV is the upper byte of this counter
+    system_counter: u16,
+
+    tima: u8,
+    tma: u8,
+    tac: u8,
+
+    // Last value of the signal whose falling edge increments TIMA
+    timer_signal: bool,
+
+    // TIMA overflowed in the last M-cycle and has to be reloaded from TMA
+    reload_pending: bool,
+
+    // DIV bit clocking the APU frame sequencer moves up in double speed
+    double_speed: bool,
+    frame_sequencer_steps: u8
+}
+
+impl Timer {
+
+    pub fn new() -> Self {
+        Self {
+            system_counter: 0,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            timer_signal: false,
+            reload_pending: false,
+            double_speed: false,
+            frame_sequencer_steps: 0
+        }
+    }
+
+    pub fn read(&self, addr: usize) -> u8 {
+        match addr {
+            DIV_ADDR => (self.system_counter >> 8) as u8,
+            TIMA_ADDR => self.tima,
+            TMA_ADDR => self.tma,
+            TAC_ADDR => 0xF8 | self.tac,
+            _ => 0xFF
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8) {
+        match addr {
+            DIV_ADDR => self.reset_divider(),
+
+            TIMA_ADDR => {
+                // Writing during the reload delay cancels the reload
+                self.tima = value;
+                self.reload_pending = false;
+            },
+
+            TMA_ADDR => self.tma = value,
+
+            TAC_ADDR => {
+                // Changing the selected bit or disabling the timer may tick TIMA
+                self.tac = value & 0x07;
+                self.update_signal();
+            },
+
+            _ => {}
+        }
+    }
+
+    pub fn reset_divider(&mut self) {
+        // The whole counter is reset, which may tick TIMA and the frame sequencer
+        let previous_counter = self.system_counter;
+
+        self.system_counter = 0;
+        self.update_signal();
+        self.update_frame_sequencer(previous_counter);
+    }
+
+    pub fn set_double_speed(&mut self, double_speed: bool) {
+        self.double_speed = double_speed;
+    }
+
+    // Frame sequencer steps since the last call
+    pub fn take_frame_sequencer_steps(&mut self) -> u8 {
+        std::mem::take(&mut self.frame_sequencer_steps)
+    }
+
+    // Returns true if the timer interrupt has to be requested
+    pub fn update(&mut self, cycles: u8) -> bool {
+        let mut interrupt = false;
+
+        for _ in 0..cycles {
+            // TIMA is reloaded one M-cycle after overflowing
+            if self.reload_pending {
+                self.reload_pending = false;
+                self.tima = self.tma;
+
+                interrupt = true;
+            }
+
+            let previous_counter = self.system_counter;
+
+            self.system_counter = self.system_counter.wrapping_add(4);
+            self.update_signal();
+            self.update_frame_sequencer(previous_counter);
+        }
+
+        interrupt
+    }
+
+    // The frame sequencer is stepped on the falling edge of DIV bit 4, or bit 5 in double speed
+    fn update_frame_sequencer(&mut self, previous_counter: u16) {
+        let selected_bit = if self.double_speed { 13 } else { 12 };
+
+        let previous_signal = (previous_counter >> selected_bit) & 0x01 != 0;
+        let signal = (self.system_counter >> selected_bit) & 0x01 != 0;
+
+        if previous_signal && !signal {
+            self.frame_sequencer_steps += 1;
+        }
+    }
+
+    fn update_signal(&mut self) {
+        let timer_enabled = (self.tac & 0x04) != 0;
+
+        let selected_bit = match self.tac & 0x03 {
+            0x00 => 9,
+            0x01 => 3,
+            0x02 => 5,
+            0x03 => 7,
+            _ => panic!("Error selecting timer bit")
+        };
+
+        let signal = timer_enabled && ((self.system_counter >> selected_bit) & 0x01) != 0;
+
+        // TIMA is incremented on the falling edge
+        if self.timer_signal && !signal {
+            let (result, overflow) = self.tima.overflowing_add(1);
+
+            self.tima = result;
+            self.reload_pending = overflow;
+        }
+
+        self.timer_signal = signal;
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Enabled, TIMA incremented every 4 M-cycles
+    const TAC_FAST: u8 = 0x05;
+
+    #[test]
+    fn tima_overflow_reloads_tma_one_cycle_later() {
+        let mut timer = Timer::new();
+        timer.write(TMA_ADDR, 0xAB);
+        timer.write(TAC_ADDR, TAC_FAST);
+        timer.write(TIMA_ADDR, 0xFF);
+
+        assert!(!timer.update(4));
+        assert_eq!(timer.read(TIMA_ADDR), 0x00);
+
+        assert!(timer.update(1));
+        assert_eq!(timer.read(TIMA_ADDR), 0xAB);
+    }
+
+    #[test]
+    fn tima_write_cancels_pending_reload() {
+        let mut timer = Timer::new();
+        timer.write(TMA_ADDR, 0xAB);
+        timer.write(TAC_ADDR, TAC_FAST);
+        timer.write(TIMA_ADDR, 0xFF);
+
+        timer.update(4);
+        timer.write(TIMA_ADDR, 0x12);
+
+        assert!(!timer.update(1));
+        assert_eq!(timer.read(TIMA_ADDR), 0x12);
+    }
+
+    #[test]
+    fn disabled_timer_doesnt_tick() {
+        let mut timer = Timer::new();
+        timer.write(TAC_ADDR, 0x01);
+
+        timer.update(255);
+
+        assert_eq!(timer.read(TIMA_ADDR), 0x00);
+    }
+
+    #[test]
+    fn div_counts_up_and_resets_on_write() {
+        let mut timer = Timer::new();
+
+        // DIV is incremented every 64 M-cycles
+        timer.update(64);
+        timer.update(64);
+        assert_eq!(timer.read(DIV_ADDR), 0x02);
+
+        timer.write(DIV_ADDR, 0x55);
+        assert_eq!(timer.read(DIV_ADDR), 0x00);
+    }
+
+    #[test]
+    fn div_reset_on_high_selected_bit_ticks_tima() {
+        let mut timer = Timer::new();
+        timer.write(TAC_ADDR, TAC_FAST);
+
+        // Counter bit 3 is set after 2 M-cycles, resetting it is a falling edge
+        timer.update(2);
+        assert_eq!(timer.read(TIMA_ADDR), 0x00);
+
+        timer.write(DIV_ADDR, 0x00);
+        assert_eq!(timer.read(TIMA_ADDR), 0x01);
+    }
+
+}
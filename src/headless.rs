@@ -0,0 +1,75 @@
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use gbc_emulator::Machine;
+
+// Test ROMs print one of these over the serial port once they are done
+const PASSED_MARKER: &[u8] = b"Passed";
+const FAILED_MARKER: &[u8] = b"Failed";
+
+// M-cycles run between serial output checks, about a frame
+const CHECK_INTERVAL_CYCLES: u32 = 17556;
+
+// How long a headless run may last
+#[derive(Clone, Copy)]
+pub enum Limit {
+    Frames(u64),
+    Cycles(u64)
+}
+
+pub enum Outcome {
+    Passed,
+    Failed,
+    LimitReached
+}
+
+// Runs the machine with no display, audio or input until the serial output
+// reports a result or the limit is reached. The output is streamed to stdout
+pub fn run(machine: &mut Machine, serial_output: &Mutex<Vec<u8>>, limit: Limit) -> Outcome {
+    let mut frames = 0;
+    let mut cycles = 0;
+    let mut printed = 0;
+
+    loop {
+        match limit {
+            Limit::Frames(max_frames) => {
+                if frames >= max_frames {
+                    return Outcome::LimitReached;
+                }
+
+                machine.run_frame();
+                frames += 1;
+            },
+
+            Limit::Cycles(max_cycles) => {
+                if cycles >= max_cycles {
+                    return Outcome::LimitReached;
+                }
+
+                let chunk = (max_cycles - cycles).min(CHECK_INTERVAL_CYCLES as u64) as u32;
+                cycles += machine.run_cycles(chunk) as u64;
+            }
+        }
+
+        let output = serial_output.lock().unwrap();
+
+        let mut stdout = io::stdout();
+        if let Err(error) = stdout.write_all(&output[printed..]).and_then(|_| stdout.flush()) {
+            eprintln!("Error writing serial output: {}", error);
+        }
+
+        printed = output.len();
+
+        if contains(&output, PASSED_MARKER) {
+            return Outcome::Passed;
+        }
+
+        if contains(&output, FAILED_MARKER) {
+            return Outcome::Failed;
+        }
+    }
+}
+
+fn contains(output: &[u8], marker: &[u8]) -> bool {
+    output.windows(marker.len()).any(|window| window == marker)
+}
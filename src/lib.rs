@@ -0,0 +1,29 @@
+// Game Boy / Game Boy Color emulator core, free of any frontend.
+//
+// A machine is built from the ROM bytes and driven by the caller one frame, or a number
+// of cycles, at a time. Input, video, audio and memory are all reachable from the outside,
+// so bots, test harnesses and other frontends can be built on top.
+
+mod gbc;
+
+pub use gbc::{
+    Machine, Model, Button,
+    CartridgeHeader, CgbSupport, HeaderError, BootRomError,
+    FrameBuffer, LCD_WIDTH, LCD_HEIGHT, FRAME_PERIOD,
+    DEFAULT_SAMPLE_RATE,
+    SerialLink, TcpLink, SerialCapture, CaptureTarget
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Harnesses and bots run machines on worker threads
+    #[test]
+    fn machine_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<Machine>();
+    }
+
+}
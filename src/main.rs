@@ -1,25 +1,222 @@
 use std::env;
 use std::process;
 use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-mod gbc;
-use gbc::GameBoyColor;
+#[cfg(feature = "frontend")]
+mod frontend;
+mod headless;
+
+use gbc_emulator::{Machine, Model, TcpLink, SerialCapture, CaptureTarget};
+#[cfg(feature = "frontend")]
+use frontend::{GameBoyColor, InputConfig};
+use headless::{Limit, Outcome};
+
+#[cfg(not(feature = "frontend"))]
+type InputConfig = ();
+
+// Headless runs give up after a minute of emulated time by default
+const DEFAULT_HEADLESS_FRAMES: u64 = 3600;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (options, paths): (Vec<&String>, Vec<&String>) = args.iter()
+        .partition(|arg| arg.starts_with("--"));
+
+    if paths.len() != 1 {
         process::exit(1);
     }
 
-    let rom_path = &args[1];
+    let rom_path = paths[0];
     println!("ROM Info:\n\t- Name: {}", rom_path);
 
-    let rom = fs::read(rom_path).unwrap();
+    let rom = match fs::read(rom_path) {
+        Ok(rom) => rom,
+        Err(error) => {
+            eprintln!("Error reading {}: {}", rom_path, error);
+            process::exit(1);
+        }
+    };
+
     println!("\t- Size: {} kB", rom.len() / 1024);
 
-    let mut gbc = GameBoyColor::new();
+    // The model is needed up front to build the machine
+    let model_name = options.iter().find_map(|option| option.strip_prefix("--model="));
+
+    let model = match model_name {
+        Some(name) => Model::from_name(name).unwrap_or_else(|| {
+            eprintln!("Unknown model: {}", name);
+            process::exit(1);
+        }),
+        None => Model::Cgb
+    };
+
+    let mut machine = Machine::new(model);
+    let mut serial_output = None;
+    let mut link_connected = false;
+
+    let mut headless = false;
+    let mut headless_limit = Limit::Frames(DEFAULT_HEADLESS_FRAMES);
+
+    #[allow(unused_mut)]
+    let mut input_config: Option<InputConfig> = None;
+
+    for option in options {
+        match option.as_str() {
+            "--color-correction" => machine.set_color_correction(true),
+            option if option.starts_with("--boot-rom=") => {
+                let boot_rom_path = &option["--boot-rom=".len()..];
+
+                let boot_rom = match fs::read(boot_rom_path) {
+                    Ok(boot_rom) => boot_rom,
+                    Err(error) => {
+                        eprintln!("Error reading {}: {}", boot_rom_path, error);
+                        process::exit(1);
+                    }
+                };
+
+                if let Err(error) = machine.load_boot_rom(boot_rom) {
+                    eprintln!("Error loading {}: {}", boot_rom_path, error);
+                    process::exit(1);
+                }
+            },
+            option if option.starts_with("--model=") => {},
+            // Streamed as it arrives or dumped when the emulator is closed
+            "--serial-output=stdout" => machine.set_serial_link(Box::new(SerialCapture::new(CaptureTarget::Stdout))),
+            "--serial-output=buffer" => {
+                let capture = SerialCapture::new(CaptureTarget::Buffer);
+                serial_output = Some(capture.buffer());
+
+                machine.set_serial_link(Box::new(capture));
+            },
+            option if option.starts_with("--link-listen=") || option.starts_with("--link-connect=") => {
+                let (mode, addr) = option.split_once('=').unwrap();
+
+                // Listening blocks until the other emulator connects
+                let link = if mode == "--link-listen" {
+                    println!("Waiting for link cable connection on {}", addr);
+                    TcpLink::listen(addr)
+                } else {
+                    TcpLink::connect(addr)
+                };
+
+                match link {
+                    Ok(link) => {
+                        machine.set_serial_link(Box::new(link));
+                        link_connected = true;
+                    },
+                    Err(error) => {
+                        eprintln!("Error connecting link cable to {}: {}", addr, error);
+                        process::exit(1);
+                    }
+                }
+            },
+            // No window, audio or input, the serial output decides when to stop
+            "--headless" => headless = true,
+            option if option.starts_with("--frames=") || option.starts_with("--cycles=") => {
+                let (name, value) = option.split_once('=').unwrap();
+
+                let Ok(value) = value.parse() else {
+                    eprintln!("Invalid {} value: {}", name, value);
+                    process::exit(1);
+                };
+
+                headless_limit = if name == "--frames" { Limit::Frames(value) } else { Limit::Cycles(value) };
+            },
+            #[cfg(feature = "frontend")]
+            option if option.starts_with("--input-config=") => {
+                let config_path = &option["--input-config=".len()..];
+
+                let config = match fs::read_to_string(config_path) {
+                    Ok(text) => InputConfig::parse(&text),
+                    Err(error) => {
+                        eprintln!("Error reading {}: {}", config_path, error);
+                        process::exit(1);
+                    }
+                };
+
+                match config {
+                    Ok(config) => input_config = Some(config),
+                    Err(error) => {
+                        eprintln!("Error loading {}: {}", config_path, error);
+                        process::exit(1);
+                    }
+                }
+            },
+            _ => {
+                eprintln!("Unknown option: {}", option);
+                process::exit(1);
+            }
+        }
+    }
+
+    if headless {
+        if link_connected {
+            eprintln!("The link cable can't be used headless");
+            process::exit(1);
+        }
+
+        // The serial port is always captured to look for the test result
+        let capture = SerialCapture::new(CaptureTarget::Buffer);
+        let serial_output = capture.buffer();
+        machine.set_serial_link(Box::new(capture));
+
+        match machine.load_rom(rom) {
+            Ok(header) => println!("{}", header),
+            Err(error) => {
+                eprintln!("Error loading {}: {}", rom_path, error);
+                process::exit(1);
+            }
+        }
+
+        let outcome = headless::run(&mut machine, &serial_output, headless_limit);
+
+        // The serial output doesn't necessarily end its last line
+        println!();
+
+        let exit_code = match outcome {
+            Outcome::Passed => 0,
+            Outcome::Failed => 1,
+            Outcome::LimitReached => {
+                eprintln!("No test result before the run limit");
+                2
+            }
+        };
+
+        process::exit(exit_code);
+    }
+
+    run_frontend(machine, rom, Path::new(rom_path), input_config, serial_output);
+}
+
+#[cfg(feature = "frontend")]
+fn run_frontend(machine: Machine, rom: Vec<u8>, rom_path: &Path, input_config: Option<InputConfig>, serial_output: Option<Arc<Mutex<Vec<u8>>>>) {
+    let mut gbc = GameBoyColor::new(machine);
+
+    // Must be set before loading the ROM for the per ROM bindings to apply
+    if let Some(config) = input_config {
+        gbc.set_input_config(config);
+    }
+
+    match gbc.load_rom(rom, rom_path) {
+        Ok(header) => println!("{}", header),
+        Err(error) => {
+            eprintln!("Error loading {}: {}", rom_path.display(), error);
+            process::exit(1);
+        }
+    }
 
-    gbc.load_rom(rom);
     gbc.run();
+
+    if let Some(serial_output) = serial_output {
+        println!("Serial output:\n{}", String::from_utf8_lossy(&serial_output.lock().unwrap()));
+    }
 }
 
+#[cfg(not(feature = "frontend"))]
+fn run_frontend(_machine: Machine, _rom: Vec<u8>, _rom_path: &Path, _input_config: Option<InputConfig>, _serial_output: Option<Arc<Mutex<Vec<u8>>>>) {
+    eprintln!("Built without the SDL frontend, only --headless runs are available");
+    process::exit(1);
+}